@@ -0,0 +1,73 @@
+//! Compares the SAT solver's clue assumption orders on nearly-contradictory
+//! puzzles: each sample puzzle with one more given that no unit rules out
+//! directly, but that no solution allows. `solve_assuming_clues` has to prove
+//! each of them UNSAT.
+//!
+//! Run with `cargo bench --bench clue_order`; prints the best of several runs.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use sudoku_sat::grid::can_place;
+use sudoku_sat::puzzles::sample_puzzle;
+use sudoku_sat::solver::logic::Difficulty;
+use sudoku_sat::solver::sat::{ClueOrder, SatSudokuSolver};
+use sudoku_sat::solver::{SolveError, SudokuSolver};
+
+type Grid = [[usize; 9]; 9];
+
+/// Timed runs per order; the fastest is reported
+const RUNS: usize = 7;
+
+/// `puzzle` with its first empty cell set to the lowest digit that repeats
+/// nothing but differs from the (unique) solution
+fn nearly_contradictory(puzzle: &Grid) -> Grid {
+    let solution = SatSudokuSolver::default().solve(puzzle).unwrap();
+    let (row, col, digit) = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(row, col)| puzzle[row][col] == 0)
+        .find_map(|(row, col)| {
+            (1..=9)
+                .find(|&d| d != solution[row][col] && can_place(puzzle, row, col, d))
+                .map(|d| (row, col, d))
+        })
+        .expect("an open cell with a wrong candidate");
+    let mut out = *puzzle;
+    out[row][col] = digit;
+    out
+}
+
+fn best_time(puzzle: &Grid, order: ClueOrder) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let mut solver = SatSudokuSolver::default().with_clue_order(order);
+            let start = Instant::now();
+            let result = solver.solve_assuming_clues(black_box(puzzle));
+            let elapsed = start.elapsed();
+            assert_eq!(result, Err(SolveError::Unsatisfiable));
+            elapsed
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let orders = [
+        ("row-major", ClueOrder::RowMajor),
+        ("most constrained", ClueOrder::MostConstrained),
+    ];
+    for difficulty in Difficulty::ALL {
+        let puzzle = nearly_contradictory(&sample_puzzle(difficulty));
+        let baseline = best_time(&puzzle, ClueOrder::RowMajor);
+        for (name, order) in orders {
+            let time = best_time(&puzzle, order);
+            println!(
+                "{:<7} {:<16} {:>10.2?}  {:+.0}%",
+                difficulty.to_string(),
+                name,
+                time,
+                (time.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0
+            );
+        }
+    }
+}
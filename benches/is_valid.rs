@@ -0,0 +1,149 @@
+//! Compares the backtracking solver's placement check, a pass over each cell's
+//! precomputed peers, with a plain row/column/box scan and a mix of the two.
+//! Each check drives the same row-major search counting the solutions of the
+//! sample puzzles (up to 2).
+//!
+//! Run with `cargo bench --bench is_valid`; prints the best of several runs.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use sudoku_sat::grid::{box_cells, box_index};
+use sudoku_sat::puzzles::sample_puzzle;
+use sudoku_sat::solver::logic::Difficulty;
+
+type Grid = [[usize; 9]; 9];
+
+/// Timed runs per check; the fastest is reported
+const RUNS: usize = 7;
+
+/// Plain row, column and box scans, with no precomputation
+fn scan(grid: &Grid, row: usize, col: usize, d: usize, _: &Peers) -> bool {
+    for c in 0..9 {
+        if grid[row][c] == d {
+            return false;
+        }
+    }
+    for r in 0..9 {
+        if grid[r][col] == d {
+            return false;
+        }
+    }
+    let (br, bc) = box_cells(box_index(row, col))[0];
+    for r in br..br + 3 {
+        for c in bc..bc + 3 {
+            if grid[r][c] == d {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// The 20 distinct cells sharing a unit with each cell, and the 4 box cells
+/// outside the cell's own row and column
+struct Peers {
+    all: [[[(usize, usize); 20]; 9]; 9],
+    box_only: [[[(usize, usize); 4]; 9]; 9],
+}
+
+impl Peers {
+    fn new() -> Self {
+        let mut peers = Peers {
+            all: [[[(0, 0); 20]; 9]; 9],
+            box_only: [[[(0, 0); 4]; 9]; 9],
+        };
+        for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+            let others = box_cells(box_index(row, col))
+                .into_iter()
+                .filter(|&(r, c)| r != row && c != col);
+            let line = (0..9)
+                .filter(|&c| c != col)
+                .map(|c| (row, c))
+                .chain((0..9).filter(|&r| r != row).map(|r| (r, col)));
+            for (slot, cell) in peers.all[row][col]
+                .iter_mut()
+                .zip(line.chain(others.clone()))
+            {
+                *slot = cell;
+            }
+            for (slot, cell) in peers.box_only[row][col].iter_mut().zip(others) {
+                *slot = cell;
+            }
+        }
+        peers
+    }
+}
+
+/// One pass over all 20 precomputed peers, as `solver::backtracking::is_valid`
+/// does it
+fn flat_peers(grid: &Grid, row: usize, col: usize, d: usize, peers: &Peers) -> bool {
+    peers.all[row][col].iter().all(|&(r, c)| grid[r][c] != d)
+}
+
+/// Row and column scans, then the 4 remaining box cells from a list
+fn box_peers(grid: &Grid, row: usize, col: usize, d: usize, peers: &Peers) -> bool {
+    (0..9).all(|c| grid[row][c] != d)
+        && (0..9).all(|r| grid[r][col] != d)
+        && peers.box_only[row][col]
+            .iter()
+            .all(|&(r, c)| grid[r][c] != d)
+}
+
+type Check = fn(&Grid, usize, usize, usize, &Peers) -> bool;
+
+/// Row-major backtracking count, as `count_solutions` does it
+fn count(grid: &mut Grid, check: Check, peers: &Peers, found: &mut usize) {
+    let Some(i) = (0..81).find(|&i| grid[i / 9][i % 9] == 0) else {
+        *found += 1;
+        return;
+    };
+    let (row, col) = (i / 9, i % 9);
+    for d in 1..=9 {
+        if check(grid, row, col, d, peers) {
+            grid[row][col] = d;
+            count(grid, check, peers, found);
+            grid[row][col] = 0;
+            if *found >= 2 {
+                return;
+            }
+        }
+    }
+}
+
+fn best_time(puzzle: &Grid, check: Check, peers: &Peers) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let mut grid = *puzzle;
+            let mut found = 0;
+            let start = Instant::now();
+            count(black_box(&mut grid), check, peers, &mut found);
+            black_box(found);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let peers = Peers::new();
+    let checks: [(&str, Check); 3] = [
+        ("scan", scan),
+        ("flat peers", flat_peers),
+        ("box peers", box_peers),
+    ];
+    for difficulty in [Difficulty::Hard, Difficulty::Expert] {
+        let puzzle = sample_puzzle(difficulty);
+        let baseline = best_time(&puzzle, scan, &peers);
+        for (name, check) in checks {
+            let time = best_time(&puzzle, check, &peers);
+            println!(
+                "{:<7} {:<11} {:>10.2?}  {:+.0}%",
+                difficulty.to_string(),
+                name,
+                time,
+                (time.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0
+            );
+        }
+    }
+}
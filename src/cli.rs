@@ -0,0 +1,825 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rustsat::types::{Assignment, TernaryVal};
+use sudoku_sat::export::{to_constraint_graph, to_graded_svg, to_svg, trace_to_markdown};
+use sudoku_sat::generator::{Rng, generate, generate_fewest_clues, generate_symmetric};
+use sudoku_sat::grid::{can_place, diff, givens_mask};
+use sudoku_sat::io::{Puzzle, parse_line, parse_puzzles, sdm_puzzles, to_line, to_pretty_string};
+use sudoku_sat::puzzles::{default_puzzle, sample_puzzle};
+use sudoku_sat::solver::backtracking::{BacktrackingSudokuSolver, SolveEvent};
+use sudoku_sat::solver::check::{determinism, round_trip};
+use sudoku_sat::solver::logic::{Difficulty, solve_with_trace};
+use sudoku_sat::solver::sat::{
+    EncodingInfo, ModelError, Redundancy, SatSudokuSolver, backend_signature, cell_var,
+    repair_fewest_changes, repair_instance, var_label,
+};
+use sudoku_sat::solver::{SolveError, Solver, SolverKind, SudokuSolver, make_solver};
+
+#[cfg(feature = "server")]
+use sudoku_sat::server::serve;
+
+#[cfg(not(feature = "server"))]
+fn serve(_port: u16) -> io::Result<()> {
+    Err(io::Error::other("built without the `server` feature"))
+}
+
+/// Command-line options; with none of the output modes set the TUI is started
+pub struct Options {
+    /// Write the solved puzzle as an SVG image to this path
+    pub svg: Option<PathBuf>,
+    /// Grade the attempt in this file against the solution in the `--svg` output
+    pub grade: Option<PathBuf>,
+    /// Write the puzzle's constraint graph in Graphviz DOT to this path
+    pub dot: Option<PathBuf>,
+    /// Print the crate and SAT backend versions
+    pub version: bool,
+    /// Serve the HTTP solve endpoint on this port (requires the `server` feature)
+    pub serve: Option<u16>,
+    /// Solve every puzzle of an `.sdm` file, printing each result as it's found
+    pub batch: Option<PathBuf>,
+    /// Generate this many random puzzles and check every solver agrees on them
+    pub cross_check: Option<usize>,
+    /// Solve the puzzle this many times with every solver and check each
+    /// solver returns the same grid every run
+    pub determinism: Option<usize>,
+    /// Write this many uniquely solvable puzzles, one line each
+    pub generate: Option<usize>,
+    /// Spend this long searching for a puzzle with as few clues as possible
+    pub fewest_clues: Option<Duration>,
+    /// Clue target for `--generate`
+    pub generate_clues: usize,
+    /// Fail `--generate` unless every puzzle has exactly the `--clues` count
+    pub exact_clues: bool,
+    /// Give `--generate` puzzles 180° rotationally symmetric givens
+    pub symmetric: bool,
+    /// Output file for `--generate`; stdout if unset
+    pub out: Option<PathBuf>,
+    /// RNG seed for `--cross-check`, `--generate` and `--fewest-clues`; a time-based seed is used
+    /// (and printed) if unset
+    pub seed: Option<u64>,
+    /// Solver used by the non-interactive modes
+    pub solver: SolverKind,
+    /// Seed for shuffling the SAT clause order, for benchmarking order sensitivity
+    pub shuffle_seed: Option<u64>,
+    /// Puzzle file: one 81-cell line, a 9x9 block, or an `.sdm` collection
+    pub file: Option<PathBuf>,
+    /// Built-in sample to use when no other puzzle source is given
+    pub sample: Option<Difficulty>,
+    /// Solve the puzzle and print the solution instead of starting the TUI
+    pub solve: bool,
+    /// Open the `--file`/`--clue` puzzles in the TUI instead of solving them
+    pub tui: bool,
+    /// Extra `R{row}C{col}={digit}` placements applied on top of the puzzle
+    pub clues: Vec<Clue>,
+    /// Print the logical deductions used to solve the puzzle instead of the solution
+    pub trace: bool,
+    /// Write the logical trace as Markdown, with a grid after each step, to this path
+    pub trace_markdown: Option<PathBuf>,
+    /// Print the size of the SAT encoding for the puzzle without solving it
+    pub encoding_info: bool,
+    /// SAT-solve the `--file` puzzles under every combination of redundant
+    /// clause families and print the timings
+    pub redundancy_matrix: bool,
+    /// Solve with SAT and print the truth value of every cell/digit literal
+    pub debug_model: bool,
+    /// Solve with backtracking and print each new deepest partial grid
+    pub debug_search: bool,
+    /// Print how long the solve took, split into encoding and solving for SAT
+    pub stats: bool,
+    /// Change as few givens as possible to make the puzzle solvable
+    pub repair: bool,
+    /// Write the repair problem as a WCNF MaxSAT instance to this path
+    pub wcnf: Option<PathBuf>,
+}
+
+/// A single placement given on the command line, stored 0-indexed
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Clue {
+    pub row: usize,
+    pub col: usize,
+    pub digit: usize,
+}
+
+impl Clue {
+    /// Parse `R{row}C{col}={digit}` with 1-indexed row and column
+    fn parse(s: &str) -> Result<Self, String> {
+        let err = || format!("invalid clue {:?}, expected R<row>C<col>=<digit>", s);
+        let upper = s.to_ascii_uppercase();
+        let rest = upper.strip_prefix('R').ok_or_else(err)?;
+        let (row, rest) = rest.split_once('C').ok_or_else(err)?;
+        let (col, digit) = rest.split_once('=').ok_or_else(err)?;
+        let parse = |v: &str| match v.trim().parse::<usize>() {
+            Ok(n) if (1..=9).contains(&n) => Ok(n),
+            _ => Err(err()),
+        };
+        Ok(Clue {
+            row: parse(row)? - 1,
+            col: parse(col)? - 1,
+            digit: parse(digit)?,
+        })
+    }
+}
+
+impl std::fmt::Display for Clue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "R{}C{}={}", self.row + 1, self.col + 1, self.digit)
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            svg: None,
+            grade: None,
+            dot: None,
+            version: false,
+            serve: None,
+            batch: None,
+            cross_check: None,
+            determinism: None,
+            generate: None,
+            fewest_clues: None,
+            generate_clues: GENERATE_CLUES,
+            exact_clues: false,
+            symmetric: false,
+            out: None,
+            seed: None,
+            solver: SolverKind::Sat,
+            shuffle_seed: None,
+            file: None,
+            sample: None,
+            solve: false,
+            tui: false,
+            clues: Vec::new(),
+            trace: false,
+            trace_markdown: None,
+            encoding_info: false,
+            redundancy_matrix: false,
+            debug_model: false,
+            debug_search: false,
+            stats: false,
+            repair: false,
+            wcnf: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut opts = Options::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--version" | "-V" => opts.version = true,
+                "--serve" => {
+                    let port = value(&mut args, &arg)?;
+                    opts.serve = Some(
+                        port.parse()
+                            .map_err(|_| format!("invalid port: {}", port))?,
+                    );
+                }
+                "--batch" => opts.batch = Some(value(&mut args, &arg)?.into()),
+                "--solver" => {
+                    let name = value(&mut args, &arg)?;
+                    opts.solver = SolverKind::from_name(&name)
+                        .ok_or_else(|| format!("unknown solver: {}", name))?;
+                }
+                "--shuffle-clauses" => {
+                    let seed = value(&mut args, &arg)?;
+                    opts.shuffle_seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("invalid seed: {}", seed))?,
+                    );
+                }
+                "--cross-check" => {
+                    let n = value(&mut args, &arg)?;
+                    opts.cross_check =
+                        Some(n.parse().map_err(|_| format!("invalid count: {}", n))?);
+                }
+                "--determinism" => {
+                    let n = value(&mut args, &arg)?;
+                    opts.determinism =
+                        Some(n.parse().map_err(|_| format!("invalid count: {}", n))?);
+                }
+                "--generate" => {
+                    let n = value(&mut args, &arg)?;
+                    opts.generate = Some(n.parse().map_err(|_| format!("invalid count: {}", n))?);
+                }
+                "--fewest-clues" => {
+                    let secs = value(&mut args, &arg)?;
+                    opts.fewest_clues = Some(Duration::from_secs(
+                        secs.parse()
+                            .map_err(|_| format!("invalid seconds: {}", secs))?,
+                    ));
+                }
+                "--clues" => {
+                    let n = value(&mut args, &arg)?;
+                    opts.generate_clues = n
+                        .parse()
+                        .ok()
+                        .filter(|n| (17..=81).contains(n))
+                        .ok_or_else(|| format!("invalid clue count (17-81): {}", n))?;
+                }
+                "--exact-clues" => opts.exact_clues = true,
+                "--symmetric" => opts.symmetric = true,
+                "--out" => opts.out = Some(value(&mut args, &arg)?.into()),
+                "--seed" => {
+                    let seed = value(&mut args, &arg)?;
+                    opts.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("invalid seed: {}", seed))?,
+                    );
+                }
+                "--file" => opts.file = Some(value(&mut args, &arg)?.into()),
+                "--default" => {
+                    let name = value(&mut args, &arg)?;
+                    opts.sample = Some(
+                        Difficulty::from_name(&name)
+                            .ok_or_else(|| format!("unknown difficulty: {}", name))?,
+                    );
+                }
+                "--clue" => {
+                    let clue = Clue::parse(&value(&mut args, &arg)?)?;
+                    if let Some(prev) = opts
+                        .clues
+                        .iter()
+                        .find(|c| (c.row, c.col) == (clue.row, clue.col))
+                    {
+                        return Err(format!("duplicate clues {} and {}", prev, clue));
+                    }
+                    opts.clues.push(clue);
+                }
+                "--trace" => opts.trace = true,
+                "--trace-md" => opts.trace_markdown = Some(value(&mut args, &arg)?.into()),
+                "--encoding-info" => opts.encoding_info = true,
+                "--redundancy-matrix" => opts.redundancy_matrix = true,
+                "--debug-model" => opts.debug_model = true,
+                "--debug-search" => opts.debug_search = true,
+                "--stats" => opts.stats = true,
+                "--repair" => opts.repair = true,
+                "--wcnf" => opts.wcnf = Some(value(&mut args, &arg)?.into()),
+                "--solve" => opts.solve = true,
+                "--tui" => opts.tui = true,
+                "--svg" => opts.svg = Some(value(&mut args, &arg)?.into()),
+                "--grade" => opts.grade = Some(value(&mut args, &arg)?.into()),
+                "--dot" => opts.dot = Some(value(&mut args, &arg)?.into()),
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+        if opts.grade.is_some() && opts.svg.is_none() {
+            return Err("--grade requires --svg".into());
+        }
+        // Mirrored pairs can step over an odd target
+        if opts.exact_clues && opts.symmetric {
+            return Err("--exact-clues cannot be combined with --symmetric".into());
+        }
+        Ok(opts)
+    }
+
+    /// Whether a non-interactive mode was requested
+    pub fn is_batch(&self) -> bool {
+        self.svg.is_some()
+            || self.dot.is_some()
+            || self.version
+            || self.serve.is_some()
+            || self.batch.is_some()
+            || self.cross_check.is_some()
+            || self.determinism.is_some()
+            || self.generate.is_some()
+            || self.fewest_clues.is_some()
+            || self.solve
+            // A puzzle given on the command line is solved and printed unless `--tui` asks
+            // for the interactive grid
+            || (!self.tui && (self.file.is_some() || !self.clues.is_empty()))
+            || self.trace
+            || self.trace_markdown.is_some()
+            || self.encoding_info
+            || self.redundancy_matrix
+            || self.debug_model
+            || self.debug_search
+            || self.stats
+            || self.repair
+            || self.wcnf.is_some()
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{} requires a value", flag))
+}
+
+/// Clue target for `--cross-check` puzzles; low enough to need real search
+const CROSS_CHECK_CLUES: usize = 25;
+
+/// Environment variable holding an 81-character puzzle, used when neither
+/// `--file` nor piped stdin provides one
+pub const PUZZLE_ENV: &str = "SUDOKU_PUZZLE";
+
+/// Default clue target for `--generate`
+const GENERATE_CLUES: usize = 30;
+
+/// Puzzles `--generate` builds per output line, keeping the one with the
+/// fewest clues, when the first lands above the clue target
+const GENERATE_ATTEMPTS: usize = 5;
+
+/// Minimum gap between grids printed by `--debug-search`
+const DEBUG_SEARCH_INTERVAL: Duration = Duration::from_millis(50);
+
+pub fn run(opts: &Options) -> io::Result<()> {
+    if opts.version {
+        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        println!("SAT backend: {}", backend_signature());
+        return Ok(());
+    }
+
+    if let Some(port) = opts.serve {
+        return serve(port);
+    }
+
+    if let Some(count) = opts.cross_check {
+        let seed = opts.seed.unwrap_or_else(|| Rng::from_time().next_u64());
+        println!("cross-checking {} puzzles, seed {}", count, seed);
+        round_trip(seed, count, CROSS_CHECK_CLUES).map_err(io::Error::other)?;
+        println!("all solvers agree");
+        return Ok(());
+    }
+
+    if let Some(count) = opts.generate {
+        return generate_puzzles(count, opts);
+    }
+
+    if let Some(budget) = opts.fewest_clues {
+        let seed = opts.seed.unwrap_or_else(|| Rng::from_time().next_u64());
+        eprintln!("searching for {:?}, seed {}", budget, seed);
+        let start = Instant::now();
+        let (puzzle, attempts) = generate_fewest_clues(start + budget, &mut Rng::new(seed));
+        let clues = puzzle.iter().flatten().filter(|&&d| d != 0).count();
+        eprintln!(
+            "fewest: {} clues after {} attempts in {:.2?}",
+            clues,
+            attempts,
+            start.elapsed()
+        );
+        println!("{}", to_line(&puzzle));
+        return Ok(());
+    }
+
+    if let Some(path) = &opts.batch {
+        return run_batch(path, opts);
+    }
+
+    if opts.redundancy_matrix {
+        return redundancy_matrix(&load_puzzles(opts)?);
+    }
+
+    let Puzzle { name, grid: puzzle } = load_puzzles(opts)?.swap_remove(0);
+    if let Some(name) = &name
+        && opts.svg.is_none()
+    {
+        println!("{}", name);
+    }
+    if let Some(runs) = opts.determinism {
+        determinism(&puzzle, runs).map_err(io::Error::other)?;
+        println!("every solver returned the same grid in {} runs", runs);
+        return Ok(());
+    }
+
+    if opts.trace {
+        for (i, step) in solve_with_trace(&puzzle).iter().enumerate() {
+            println!("{:>3}. {}", i + 1, step);
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = &opts.trace_markdown {
+        let steps = solve_with_trace(&puzzle);
+        return std::fs::write(path, trace_to_markdown(&steps, Some(&puzzle)));
+    }
+
+    if let Some(path) = &opts.dot {
+        return std::fs::write(path, to_constraint_graph(&puzzle));
+    }
+
+    if opts.encoding_info {
+        print_encoding_info(&SatSudokuSolver::default().encoding_info(&puzzle));
+        return Ok(());
+    }
+
+    if let Some(path) = &opts.wcnf {
+        return repair_instance(&puzzle)
+            .map_err(io::Error::other)?
+            .write_dimacs_path(path)
+            .map_err(|e| io::Error::other(e.to_string()));
+    }
+
+    if opts.repair {
+        let (changes, repaired) = repair_fewest_changes(&puzzle).map_err(io::Error::other)?;
+        println!("{} given(s) changed", changes);
+        for (row, col, old, new) in diff(&puzzle, &repaired) {
+            if old != 0 {
+                println!("  R{}C{}: {} -> {}", row + 1, col + 1, old, new);
+            }
+        }
+        print!("{}", to_pretty_string(&repaired));
+        return Ok(());
+    }
+
+    let solution = if opts.debug_model {
+        let mut solver = SatSudokuSolver::default();
+        let (grid, model) = solver
+            .solve_with_assignment(&puzzle)
+            .map_err(io::Error::other)?;
+        print_model(&model);
+        warn_model_error(solver.last_model_error());
+        grid
+    } else if opts.debug_search {
+        let mut last_print: Option<Instant> = None;
+        BacktrackingSudokuSolver::default()
+            .with_observer(Box::new(move |event| {
+                let SolveEvent::NewDeepest { depth, grid } = event;
+                if last_print.is_some_and(|t| t.elapsed() < DEBUG_SEARCH_INTERVAL) {
+                    return;
+                }
+                last_print = Some(Instant::now());
+                println!("depth {}", depth);
+                print!("{}", to_pretty_string(grid));
+            }))
+            .solve(&puzzle)
+            .map_err(io::Error::other)?
+    } else if opts.stats {
+        let mut solver = configured_solver(opts).with_timings();
+        let start = Instant::now();
+        let grid = solver.solve(&puzzle).map_err(io::Error::other)?;
+        println!("total:  {:.2?}", start.elapsed());
+        if let Some(times) = solver.sat_times() {
+            println!("encode: {:.2?}", times.encode_time);
+            println!("solve:  {:.2?}", times.solve_time);
+        }
+        warn_model_error(solver.sat_model_error());
+        grid
+    } else {
+        let mut solver = configured_solver(opts);
+        let grid = solver.solve(&puzzle).map_err(io::Error::other)?;
+        warn_model_error(solver.sat_model_error());
+        grid
+    };
+
+    if let Some(path) = &opts.svg {
+        let svg = match &opts.grade {
+            Some(attempt) => {
+                let attempt = parse_puzzles(&std::fs::read_to_string(attempt)?)?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "attempt is empty")
+                    })?;
+                to_graded_svg(&puzzle, &attempt.grid, &solution)
+            }
+            None => to_svg(&solution, &givens_mask(&puzzle)),
+        };
+        std::fs::write(path, svg)?;
+    } else {
+        print!("{}", to_pretty_string(&solution));
+        println!("{}", solve_summary(&puzzle, &solution));
+    }
+    Ok(())
+}
+
+/// Flag a SAT model that wasn't a clean grid, which means an encoding bug
+fn warn_model_error(err: Option<&ModelError>) {
+    if let Some(err) = err {
+        eprintln!("warning: {}", err);
+    }
+}
+
+/// e.g. "Filled 51 cells; 30 givens preserved.", flagging any given the
+/// solution changed
+fn solve_summary(puzzle: &[[usize; 9]; 9], solution: &[[usize; 9]; 9]) -> String {
+    let changes = diff(puzzle, solution);
+    let filled = changes.iter().filter(|&&(_, _, old, _)| old == 0).count();
+    let changed = changes.len() - filled;
+    let givens = puzzle.iter().flatten().filter(|&&d| d != 0).count();
+    let mut summary = format!(
+        "Filled {} cells; {} givens preserved.",
+        filled,
+        givens - changed
+    );
+    if changed > 0 {
+        summary += &format!(" {} givens changed!", changed);
+    }
+    summary
+}
+
+/// `--generate`: write `count` puzzles to `--out` (or stdout), reporting a
+/// summary, and progress when writing to a file, on stderr
+fn generate_puzzles(count: usize, opts: &Options) -> io::Result<()> {
+    let seed = opts.seed.unwrap_or_else(|| Rng::from_time().next_u64());
+    eprintln!("generating {} puzzles, seed {}", count, seed);
+    let mut rng = Rng::new(seed);
+    let mut out: Box<dyn Write> = match &opts.out {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let target = opts.generate_clues;
+    let start = Instant::now();
+    let mut clue_counts = Vec::with_capacity(count);
+    for i in 0..count {
+        let mut best: Option<([[usize; 9]; 9], usize)> = None;
+        for _ in 0..GENERATE_ATTEMPTS {
+            let puzzle = if opts.symmetric {
+                generate_symmetric(target, &mut rng)
+            } else {
+                generate(target, &mut rng)
+            };
+            let clues = puzzle.iter().flatten().filter(|&&d| d != 0).count();
+            if best.is_none_or(|(_, fewest)| clues < fewest) {
+                best = Some((puzzle, clues));
+            }
+            if clues <= target {
+                break;
+            }
+        }
+        let (puzzle, clues) = best.expect("at least one attempt");
+        if opts.exact_clues && clues != target {
+            return Err(io::Error::other(format!(
+                "puzzle {}: no unique puzzle with exactly {} clues in {} attempts (fewest {})",
+                i + 1,
+                target,
+                GENERATE_ATTEMPTS,
+                clues
+            )));
+        }
+        writeln!(out, "{}", to_line(&puzzle))?;
+        clue_counts.push(clues);
+        // Progress would interleave with the puzzles when both go to the terminal
+        if opts.out.is_some() {
+            eprint!("\r{}/{}", i + 1, count);
+        }
+    }
+    out.flush()?;
+    if opts.out.is_some() && count > 0 {
+        eprintln!();
+    }
+
+    let above = clue_counts.iter().filter(|&&n| n > target).count();
+    let (min, max) = (
+        clue_counts.iter().min().copied().unwrap_or(0),
+        clue_counts.iter().max().copied().unwrap_or(0),
+    );
+    let mean = clue_counts.iter().sum::<usize>() as f64 / count.max(1) as f64;
+    eprintln!(
+        "{} puzzles in {:.2?}; clues min {}, mean {:.1}, max {} ({} above the target of {})",
+        count,
+        start.elapsed(),
+        min,
+        mean,
+        max,
+        above,
+        target
+    );
+    Ok(())
+}
+
+/// The `--solver` backend with the tuning flags applied
+fn configured_solver(opts: &Options) -> Solver {
+    let solver = make_solver(opts.solver);
+    match opts.shuffle_seed {
+        Some(seed) => solver.with_shuffled_clauses(seed),
+        None => solver,
+    }
+}
+
+fn print_encoding_info(info: &EncodingInfo) {
+    println!("{:<20}{:>8}", "variables", info.vars);
+    println!("{:<20}{:>8}", "clauses", info.total_clauses());
+    for (label, n) in [
+        ("cell-at-least-one", info.cell_at_least_one),
+        ("row-amo", info.row_amo),
+        ("col-amo", info.col_amo),
+        ("box-amo", info.box_amo),
+        ("redundant", info.redundant),
+        ("clues", info.clues),
+    ] {
+        println!("  {:<18}{:>8}", label, n);
+    }
+}
+
+/// Solve every puzzle once per `Redundancy` combination, one line each with
+/// the clause count and the total time
+fn redundancy_matrix(puzzles: &[Puzzle]) -> io::Result<()> {
+    println!("{} puzzles", puzzles.len());
+    println!("{:<34}{:>8}{:>12}", "redundant clauses", "clauses", "time");
+    for redundancy in Redundancy::combinations() {
+        let mut solver = SatSudokuSolver::default().with_redundancy(redundancy);
+        let start = Instant::now();
+        for puzzle in puzzles {
+            match solver.solve(&puzzle.grid) {
+                Ok(_) | Err(SolveError::Unsatisfiable) => {}
+                Err(e) => return Err(io::Error::other(e)),
+            }
+        }
+        println!(
+            "{:<34}{:>8}{:>12}",
+            redundancy.to_string(),
+            solver.estimated_clause_count(),
+            format!("{:.2?}", start.elapsed())
+        );
+    }
+    Ok(())
+}
+
+/// One line per encoding literal, e.g. `R1C1=5 (var 5): true`
+fn print_model(model: &Assignment) {
+    for row in 0..9 {
+        for col in 0..9 {
+            for digit in 1..=9 {
+                let var = cell_var(row, col, digit);
+                let value = match model[var] {
+                    TernaryVal::True => "true",
+                    TernaryVal::False => "false",
+                    TernaryVal::DontCare => "unassigned",
+                };
+                let label = var_label(var).unwrap_or_default();
+                println!("{} (var {}): {}", label, var.idx() + 1, value);
+            }
+        }
+    }
+}
+
+/// Puzzles from the first source that has any (see `read_puzzles`), with
+/// `--clue` placements applied to the first one
+pub fn load_puzzles(opts: &Options) -> io::Result<Vec<Puzzle>> {
+    let mut puzzles = read_puzzles(opts, piped_stdin, std::env::var(PUZZLE_ENV).ok())?;
+    let Some(Puzzle { grid: puzzle, .. }) = puzzles.first_mut() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "puzzle file is empty",
+        ));
+    };
+    for clue in &opts.clues {
+        // Clues override whatever the file had in that cell
+        puzzle[clue.row][clue.col] = 0;
+        if !can_place(puzzle, clue.row, clue.col, clue.digit) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("clue {} conflicts with the puzzle", clue),
+            ));
+        }
+        puzzle[clue.row][clue.col] = clue.digit;
+    }
+    Ok(puzzles)
+}
+
+/// Puzzles from `--file`, else piped stdin, else the `SUDOKU_PUZZLE` value
+/// `env`, else the `--sample` (or default) puzzle. Clues alone start from an
+/// empty grid instead of a sample. `stdin` is only read when there is no file.
+fn read_puzzles(
+    opts: &Options,
+    stdin: impl FnOnce() -> io::Result<Option<String>>,
+    env: Option<String>,
+) -> io::Result<Vec<Puzzle>> {
+    if let Some(path) = &opts.file {
+        return parse_puzzles(&std::fs::read_to_string(path)?);
+    }
+    if let Some(text) = stdin()? {
+        return parse_puzzles(&text);
+    }
+    Ok(match env {
+        Some(line) => vec![Puzzle::from(parse_line(&line)?)],
+        None if !opts.clues.is_empty() => vec![Puzzle::from([[0; 9]; 9])],
+        None => vec![Puzzle::from(
+            opts.sample.map_or_else(default_puzzle, sample_puzzle),
+        )],
+    })
+}
+
+/// Solve an `.sdm` file line by line, printing each result immediately so
+/// memory stays flat and progress is visible on large files
+fn run_batch(path: &Path, opts: &Options) -> io::Result<()> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut solver = configured_solver(opts);
+    let mut times = Vec::new();
+    for (line, puzzle) in sdm_puzzles(reader) {
+        let puzzle = match puzzle {
+            Ok(p) => p,
+            Err(e) => {
+                println!("{}: error: {}", line, e);
+                continue;
+            }
+        };
+        let start = Instant::now();
+        let solution = solver.solve(&puzzle);
+        let elapsed = start.elapsed();
+        times.push(elapsed);
+        let ms = elapsed.as_millis();
+        match solution {
+            Ok(grid) => println!("{}: {} ({} ms)", line, to_line(&grid), ms),
+            Err(SolveError::Unsatisfiable) => println!("{}: no solution ({} ms)", line, ms),
+            Err(e) => println!("{}: error: {} ({} ms)", line, e, ms),
+        }
+    }
+    print_time_histogram(&mut times);
+    Ok(())
+}
+
+/// Upper bounds of the solve-time histogram buckets; the last bucket is open
+const HISTOGRAM_BOUNDS: [Duration; 3] = [
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+];
+
+/// Widest histogram bar, in characters
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// Min, median and max of the solve times, then how many fell in each bucket
+fn print_time_histogram(times: &mut [Duration]) {
+    if times.is_empty() {
+        return;
+    }
+    times.sort();
+    println!(
+        "\n{} solves: min {:.2?}, median {:.2?}, max {:.2?}",
+        times.len(),
+        times[0],
+        times[times.len() / 2],
+        times[times.len() - 1]
+    );
+    let mut counts = [0; HISTOGRAM_BOUNDS.len() + 1];
+    for t in times.iter() {
+        counts[HISTOGRAM_BOUNDS.partition_point(|bound| bound <= t)] += 1;
+    }
+    let labels = ["<1ms", "1-10ms", "10-100ms", ">=100ms"];
+    let most = counts.iter().copied().max().unwrap_or(0);
+    for (label, count) in labels.iter().zip(counts) {
+        // Any non-empty bucket gets at least one mark
+        let bar = (count * HISTOGRAM_WIDTH).div_ceil(most);
+        let row = format!("{:<10}{:>6} {}", label, count, "#".repeat(bar));
+        println!("{}", row.trim_end());
+    }
+}
+
+/// Everything piped into stdin; `None` when stdin is a terminal or the pipe
+/// is empty, so `</dev/null` in CI falls through to the next source
+fn piped_stdin() -> io::Result<Option<String>> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        return Ok(None);
+    }
+    let text = io::read_to_string(stdin)?;
+    Ok((!text.trim().is_empty()).then_some(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Options {
+        Options::parse(args.iter().map(|a| a.to_string())).unwrap()
+    }
+
+    #[test]
+    fn file_solves_and_prints_unless_tui_is_given() {
+        assert!(parse(&["--file", "p.txt"]).is_batch());
+        assert!(parse(&["--clue", "R1C1=5"]).is_batch());
+        assert!(!parse(&["--file", "p.txt", "--tui"]).is_batch());
+        assert!(!parse(&["--clue", "R1C1=5", "--tui"]).is_batch());
+    }
+
+    /// The first puzzle `read_puzzles` picks given these stdin and env grids
+    fn first_grid(
+        opts: &Options,
+        stdin: Option<[[usize; 9]; 9]>,
+        env: Option<[[usize; 9]; 9]>,
+    ) -> [[usize; 9]; 9] {
+        let stdin = || Ok(stdin.map(|g| to_line(&g)));
+        read_puzzles(opts, stdin, env.map(|g| to_line(&g))).unwrap()[0].grid
+    }
+
+    #[test]
+    fn puzzle_sources_take_file_then_stdin_then_env_then_default() {
+        let [file, piped, env] =
+            [Difficulty::Easy, Difficulty::Hard, Difficulty::Expert].map(sample_puzzle);
+        let path = std::env::temp_dir().join(format!("sources-{}.txt", std::process::id()));
+        std::fs::write(&path, to_line(&file)).unwrap();
+        let with_file = parse(&["--file", path.to_str().unwrap()]);
+        assert_eq!(first_grid(&with_file, Some(piped), Some(env)), file);
+        std::fs::remove_file(&path).unwrap();
+
+        let opts = parse(&[]);
+        assert_eq!(first_grid(&opts, Some(piped), Some(env)), piped);
+        assert_eq!(first_grid(&opts, None, Some(env)), env);
+        assert_eq!(first_grid(&opts, None, None), default_puzzle());
+        let clues = parse(&["--clue", "R1C1=5"]);
+        assert_eq!(first_grid(&clues, None, None), [[0; 9]; 9]);
+    }
+
+    #[test]
+    fn a_malformed_env_puzzle_is_an_error() {
+        let err = read_puzzles(&parse(&[]), || Ok(None), Some("12".into())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt::Write;
+
+use crate::grid::{box_index, diff};
+use crate::io::to_pretty_string;
+use crate::solver::logic::{SolveStep, Technique};
+
+const CELL: usize = 50;
+const MARGIN: usize = 10;
+const SIZE: usize = CELL * 9 + MARGIN * 2;
+
+/// Render a grid as a standalone SVG image.
+///
+/// Givens are drawn in black and every other filled cell in blue;
+/// lines between 3x3 boxes are drawn heavier than the cell lines.
+pub fn to_svg(grid: &[[usize; 9]; 9], givens: &[[bool; 9]; 9]) -> String {
+    let mut svg = svg_frame();
+    for row in 0..9 {
+        for col in 0..9 {
+            let digit = grid[row][col];
+            if digit == 0 {
+                continue;
+            }
+            let fill = if givens[row][col] { "black" } else { "#1f5fbf" };
+            svg_digit(&mut svg, row, col, digit, fill);
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render a user's attempt graded against the solution, as a standalone SVG.
+///
+/// Givens are black and the user's entries blue, with a green check on each
+/// correct entry and a red X over each wrong one. Cells the user left empty
+/// show the solution's digit in light grey.
+pub fn to_graded_svg(
+    givens: &[[usize; 9]; 9],
+    attempt: &[[usize; 9]; 9],
+    solution: &[[usize; 9]; 9],
+) -> String {
+    let mut svg = svg_frame();
+    for row in 0..9 {
+        for col in 0..9 {
+            if givens[row][col] != 0 {
+                svg_digit(&mut svg, row, col, givens[row][col], "black");
+            } else if attempt[row][col] == 0 {
+                svg_digit(&mut svg, row, col, solution[row][col], "#c0c0c0");
+            } else {
+                svg_digit(&mut svg, row, col, attempt[row][col], "#1f5fbf");
+                if attempt[row][col] == solution[row][col] {
+                    svg_check(&mut svg, row, col);
+                }
+            }
+        }
+    }
+    // Drawn last so the crosses sit on top of the digits
+    for (row, col, entered, _) in diff(attempt, solution) {
+        if givens[row][col] == 0 && entered != 0 {
+            svg_cross(&mut svg, row, col);
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Opening tag, white background and grid lines, heavier on box boundaries
+fn svg_frame() -> String {
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SIZE}" height="{SIZE}" viewBox="0 0 {SIZE} {SIZE}">"#
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"<rect width="{SIZE}" height="{SIZE}" fill="white"/>"#
+    )
+    .unwrap();
+
+    for i in 0..=9 {
+        let pos = MARGIN + i * CELL;
+        let width = if i % 3 == 0 { 3 } else { 1 };
+        let (start, end) = (MARGIN, MARGIN + 9 * CELL);
+        writeln!(
+            svg,
+            r#"<line x1="{start}" y1="{pos}" x2="{end}" y2="{pos}" stroke="black" stroke-width="{width}"/>"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<line x1="{pos}" y1="{start}" x2="{pos}" y2="{end}" stroke="black" stroke-width="{width}"/>"#
+        )
+        .unwrap();
+    }
+    svg
+}
+
+fn svg_digit(svg: &mut String, row: usize, col: usize, digit: usize, fill: &str) {
+    let x = MARGIN + col * CELL + CELL / 2;
+    let y = MARGIN + row * CELL + CELL / 2;
+    writeln!(
+        svg,
+        r#"<text x="{x}" y="{y}" font-family="sans-serif" font-size="30" text-anchor="middle" dominant-baseline="central" fill="{fill}">{digit}</text>"#
+    )
+    .unwrap();
+}
+
+/// Small green tick in the cell's top-right corner
+fn svg_check(svg: &mut String, row: usize, col: usize) {
+    let (x, y) = (MARGIN + col * CELL, MARGIN + row * CELL);
+    writeln!(
+        svg,
+        r##"<polyline points="{},{} {},{} {},{}" fill="none" stroke="#2e9e3e" stroke-width="3"/>"##,
+        x + CELL - 18,
+        y + 10,
+        x + CELL - 13,
+        y + 15,
+        x + CELL - 5,
+        y + 5
+    )
+    .unwrap();
+}
+
+/// Red X across the whole cell
+fn svg_cross(svg: &mut String, row: usize, col: usize) {
+    let (x, y) = (MARGIN + col * CELL, MARGIN + row * CELL);
+    let (near, far) = (8, CELL - 8);
+    for (x1, y1, x2, y2) in [(near, near, far, far), (near, far, far, near)] {
+        writeln!(
+            svg,
+            r##"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="#d62728" stroke-width="3"/>"##,
+            x + x1,
+            y + y1,
+            x + x2,
+            y + y2
+        )
+        .unwrap();
+    }
+}
+
+/// Render a puzzle's constraint graph in Graphviz DOT.
+///
+/// Nodes are the cells, named `r1c1` to `r9c9` and pinned in grid layout (for
+/// `neato -n`); givens are labelled with their digit. Each pair of cells
+/// sharing a row, column or box gets one edge, listed in row-major order.
+pub fn to_constraint_graph(puzzle: &[[usize; 9]; 9]) -> String {
+    let mut dot = String::new();
+    writeln!(dot, "graph sudoku {{").unwrap();
+    writeln!(dot, "  node [shape=circle, width=0.4, fixedsize=true];").unwrap();
+    for (row, cells) in puzzle.iter().enumerate() {
+        for (col, &d) in cells.iter().enumerate() {
+            let label = match d {
+                0 => String::new(),
+                d => d.to_string(),
+            };
+            let (x, y) = (col * CELL, (8 - row) * CELL);
+            writeln!(
+                dot,
+                r#"  r{}c{} [label="{label}", pos="{x},{y}"];"#,
+                row + 1,
+                col + 1
+            )
+            .unwrap();
+        }
+    }
+    for a in 0..81 {
+        let (r1, c1) = (a / 9, a % 9);
+        for b in (a + 1)..81 {
+            let (r2, c2) = (b / 9, b % 9);
+            let same_box = box_index(r1, c1) == box_index(r2, c2);
+            if r1 == r2 || c1 == c2 || same_box {
+                writeln!(dot, "  r{}c{} -- r{}c{};", r1 + 1, c1 + 1, r2 + 1, c2 + 1).unwrap();
+            }
+        }
+    }
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+/// Technique name in bold, with where it applies
+fn technique_markdown(technique: Technique) -> String {
+    match technique {
+        Technique::NakedSingle => "**Naked single**".to_string(),
+        Technique::HiddenSingle(unit) => format!("**Hidden single** in {}", unit),
+        Technique::XWing { digit, lines } => {
+            format!("**X-Wing** on {} in {} and {}", digit, lines[0], lines[1])
+        }
+    }
+}
+
+/// Render a logical-solver trace as a numbered Markdown list, for tutorials.
+///
+/// Each item names the technique and the cell it fills or the candidates it
+/// rules out. With `puzzle`, every
+/// placement is followed by a code block of the grid after that step.
+pub fn trace_to_markdown(steps: &[SolveStep], puzzle: Option<&[[usize; 9]; 9]>) -> String {
+    let mut md = String::new();
+    let mut grid = puzzle.copied();
+    for (i, step) in steps.iter().enumerate() {
+        let n = i + 1;
+        match *step {
+            SolveStep::Place {
+                row,
+                col,
+                digit,
+                technique,
+            } => {
+                let how = technique_markdown(technique);
+                writeln!(md, "{n}. {how}: R{}C{} = {digit}", row + 1, col + 1).unwrap();
+                if let Some(grid) = &mut grid {
+                    grid[row][col] = digit;
+                    writeln!(md).unwrap();
+                    writeln!(md, "   ```").unwrap();
+                    for line in to_pretty_string(grid).lines() {
+                        writeln!(md, "   {line}").unwrap();
+                    }
+                    writeln!(md, "   ```").unwrap();
+                    writeln!(md).unwrap();
+                }
+            }
+            SolveStep::Eliminate {
+                cell: (row, col),
+                ref digits,
+                technique,
+            } => {
+                let how = technique_markdown(technique);
+                let digits: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+                let digits = digits.join(", ");
+                writeln!(md, "{n}. {how}: R{}C{} ≠ {digits}", row + 1, col + 1).unwrap();
+            }
+            SolveStep::GuessRequired { empty } => {
+                writeln!(md, "{n}. **Guessing required**: {empty} cells left").unwrap();
+            }
+        }
+    }
+    md
+}
@@ -0,0 +1,242 @@
+use std::time::Instant;
+
+use crate::grid::can_place;
+use crate::solver::backtracking::{UniqueResult, unique_solution};
+use crate::solver::logic::{Difficulty, estimate_difficulty};
+
+/// Small seedable xorshift64* generator, so puzzle generation is reproducible
+/// without pulling in an RNG crate
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 scrambles the seed so small seeds still give good streams
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Rng((z ^ (z >> 31)) | 1)
+    }
+
+    /// Seed taken from the system clock
+    pub fn from_time() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Rng::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform-ish value in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// A random complete, valid grid
+pub fn random_solution(rng: &mut Rng) -> [[usize; 9]; 9] {
+    let mut grid = [[0usize; 9]; 9];
+    fill(&mut grid, 0, rng);
+    grid
+}
+
+fn fill(grid: &mut [[usize; 9]; 9], pos: usize, rng: &mut Rng) -> bool {
+    if pos == 81 {
+        return true;
+    }
+    let (row, col) = (pos / 9, pos % 9);
+    let mut digits = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+    rng.shuffle(&mut digits);
+    for d in digits {
+        if can_place(grid, row, col, d) {
+            grid[row][col] = d;
+            if fill(grid, pos + 1, rng) {
+                return true;
+            }
+            grid[row][col] = 0;
+        }
+    }
+    false
+}
+
+/// Generate a puzzle with a unique solution.
+///
+/// Cells are removed in random order as long as the solution stays unique,
+/// stopping at `clues` givens; `clues` is a target, harder puzzles may keep more.
+pub fn generate(clues: usize, rng: &mut Rng) -> [[usize; 9]; 9] {
+    remove_clues(clues, false, rng)
+}
+
+/// Like `generate`, but cells are removed in pairs mirrored through the centre,
+/// so the givens have 180° rotational symmetry
+pub fn generate_symmetric(clues: usize, rng: &mut Rng) -> [[usize; 9]; 9] {
+    remove_clues(clues, true, rng)
+}
+
+/// Fewest givens any uniquely solvable 9x9 puzzle can have
+pub const MIN_CLUES: usize = 17;
+
+/// Generate puzzles until `deadline`, keeping the one with the fewest clues,
+/// and return it with the number of attempts made.
+///
+/// Each attempt removes cells from a fresh random solution until every
+/// remaining clue is needed for uniqueness, which usually stops in the low
+/// twenties; the search ends early if it ever reaches `MIN_CLUES`, which is
+/// rare. At least one attempt is made.
+pub fn generate_fewest_clues(deadline: Instant, rng: &mut Rng) -> ([[usize; 9]; 9], usize) {
+    let count = |p: &[[usize; 9]; 9]| p.iter().flatten().filter(|&&d| d != 0).count();
+    let mut best = generate(MIN_CLUES, rng);
+    let mut attempts = 1;
+    while count(&best) > MIN_CLUES && Instant::now() < deadline {
+        let puzzle = generate(MIN_CLUES, rng);
+        attempts += 1;
+        if count(&puzzle) < count(&best) {
+            best = puzzle;
+        }
+    }
+    (best, attempts)
+}
+
+/// How strictly `generate_with_target` holds to its clue count
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ClueTarget {
+    /// Remove cells down to this many givens, keeping more if every further
+    /// removal would break uniqueness
+    AtMost(usize),
+    /// Exactly this many givens, or no puzzle
+    Exactly(usize),
+}
+
+/// What `generate_with_target` achieved
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Generated {
+    /// A unique puzzle with exactly the requested number of givens
+    Reached([[usize; 9]; 9]),
+    /// Removal got stuck with `clues` givens left; the puzzle is still unique.
+    /// Only returned for `AtMost`
+    Above {
+        puzzle: [[usize; 9]; 9],
+        clues: usize,
+    },
+    /// `Exactly` got stuck with `clues` givens left
+    Failed { clues: usize },
+}
+
+/// Generate a unique puzzle and report whether the clue target was met.
+///
+/// Both modes remove cells in random order while uniqueness holds; they only
+/// differ in what happens when removal stalls above the target. A target
+/// below 17 can never be reached.
+pub fn generate_with_target(target: ClueTarget, rng: &mut Rng) -> Generated {
+    let (ClueTarget::AtMost(wanted) | ClueTarget::Exactly(wanted)) = target;
+    let puzzle = remove_clues(wanted, false, rng);
+    let clues = puzzle.iter().flatten().filter(|&&d| d != 0).count();
+    match target {
+        ClueTarget::Exactly(_) if clues != wanted => Generated::Failed { clues },
+        _ if clues <= wanted => Generated::Reached(puzzle),
+        _ => Generated::Above { puzzle, clues },
+    }
+}
+
+fn remove_clues(clues: usize, symmetric: bool, rng: &mut Rng) -> [[usize; 9]; 9] {
+    let mut puzzle = random_solution(rng);
+    // With symmetry only the first half (and the centre) is picked; each pick
+    // also takes its mirror cell `80 - pos`
+    let mut cells: Vec<usize> = (0..if symmetric { 41 } else { 81 }).collect();
+    rng.shuffle(&mut cells);
+
+    let mut filled = 81;
+    for pos in cells {
+        if filled <= clues {
+            break;
+        }
+        let group = if symmetric && pos != 40 {
+            vec![pos, 80 - pos]
+        } else {
+            vec![pos]
+        };
+        let digits: Vec<usize> = group.iter().map(|&p| puzzle[p / 9][p % 9]).collect();
+        for &p in &group {
+            puzzle[p / 9][p % 9] = 0;
+        }
+        if matches!(unique_solution(&puzzle), UniqueResult::Unique(_)) {
+            filled -= group.len();
+        } else {
+            for (&p, &digit) in group.iter().zip(&digits) {
+                puzzle[p / 9][p % 9] = digit;
+            }
+        }
+    }
+    puzzle
+}
+
+/// Add clues to `partial` until it has exactly one solution; `None` if it has
+/// no solution at all.
+///
+/// The added clues all come from one solution. While another solution
+/// remains, a random cell where it differs gets the kept solution's digit;
+/// afterwards each added clue is dropped again if uniqueness survives, so
+/// none of them is redundant (though a smaller set may exist).
+pub fn complete_to_unique(partial: &[[usize; 9]; 9], rng: &mut Rng) -> Option<[[usize; 9]; 9]> {
+    let mut puzzle = *partial;
+    let target = match unique_solution(&puzzle) {
+        UniqueResult::None => return None,
+        UniqueResult::Unique(_) => return Some(puzzle),
+        UniqueResult::Multiple(pair) => pair[rng.below(2)],
+    };
+
+    let mut added = Vec::new();
+    while let UniqueResult::Multiple(pair) = unique_solution(&puzzle) {
+        let other = if pair[0] == target { pair[1] } else { pair[0] };
+        let differing: Vec<usize> = (0..81)
+            .filter(|&i| other[i / 9][i % 9] != target[i / 9][i % 9])
+            .collect();
+        let pos = differing[rng.below(differing.len())];
+        puzzle[pos / 9][pos % 9] = target[pos / 9][pos % 9];
+        added.push(pos);
+    }
+
+    rng.shuffle(&mut added);
+    for pos in added {
+        puzzle[pos / 9][pos % 9] = 0;
+        if !matches!(unique_solution(&puzzle), UniqueResult::Unique(_)) {
+            puzzle[pos / 9][pos % 9] = target[pos / 9][pos % 9];
+        }
+    }
+    Some(puzzle)
+}
+
+/// Clue target handed to `generate` when aiming for a difficulty; fewer
+/// clues make harder puzzles more likely
+fn clue_target(target: Difficulty) -> usize {
+    match target {
+        Difficulty::Easy => 36,
+        Difficulty::Medium => 30,
+        Difficulty::Hard => 26,
+        Difficulty::Expert => 22,
+    }
+}
+
+/// Generate puzzles until one rates as `target`, giving up with `None` after
+/// `max_attempts` tries
+pub fn generate_with_difficulty(
+    target: Difficulty,
+    max_attempts: usize,
+    rng: &mut Rng,
+) -> Option<[[usize; 9]; 9]> {
+    (0..max_attempts)
+        .map(|_| generate(clue_target(target), rng))
+        .find(|puzzle| estimate_difficulty(puzzle) == Some(target))
+}
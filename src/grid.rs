@@ -0,0 +1,285 @@
+use crate::solver::Variant;
+
+/// Every cell where the grids differ, as `(row, col, a_val, b_val)` in row-major order
+pub fn diff(a: &[[usize; 9]; 9], b: &[[usize; 9]; 9]) -> Vec<(usize, usize, usize, usize)> {
+    let mut out = Vec::new();
+    for row in 0..9 {
+        for col in 0..9 {
+            if a[row][col] != b[row][col] {
+                out.push((row, col, a[row][col], b[row][col]));
+            }
+        }
+    }
+    out
+}
+
+/// Mask of the filled cells, used to mark which cells are the puzzle's givens
+pub fn givens_mask(grid: &[[usize; 9]; 9]) -> [[bool; 9]; 9] {
+    let mut mask = [[false; 9]; 9];
+    for row in 0..9 {
+        for col in 0..9 {
+            mask[row][col] = grid[row][col] != 0;
+        }
+    }
+    mask
+}
+
+/// Index 0-8 of the box holding `(row, col)`, numbered row-major
+pub const fn box_index(row: usize, col: usize) -> usize {
+    (row / 3) * 3 + col / 3
+}
+
+/// The cells of box `box_idx` (as numbered by `box_index`), row-major
+pub const fn box_cells(box_idx: usize) -> [(usize, usize); 9] {
+    let (top, left) = ((box_idx / 3) * 3, (box_idx % 3) * 3);
+    let mut cells = [(0, 0); 9];
+    let mut i = 0;
+    while i < 9 {
+        cells[i] = (top + i / 3, left + i % 3);
+        i += 1;
+    }
+    cells
+}
+
+/// All 27 units as cell coordinates: rows 0-8, columns 9-17, boxes 18-26
+pub const UNITS: [[(usize, usize); 9]; 27] = build_units();
+
+const fn build_units() -> [[(usize, usize); 9]; 27] {
+    let mut units = [[(0, 0); 9]; 27];
+    let mut i = 0;
+    while i < 9 {
+        let mut j = 0;
+        while j < 9 {
+            units[i][j] = (i, j);
+            units[9 + i][j] = (j, i);
+            j += 1;
+        }
+        units[18 + i] = box_cells(i);
+        i += 1;
+    }
+    units
+}
+
+/// Rows, columns and boxes of a standard grid
+pub fn units() -> &'static [[(usize, usize); 9]; 27] {
+    &UNITS
+}
+
+/// Units that apply under a variant (Latin squares have no boxes)
+pub fn variant_units(variant: Variant) -> &'static [[(usize, usize); 9]] {
+    if variant.has_boxes() {
+        &UNITS
+    } else {
+        &UNITS[..18]
+    }
+}
+
+/// True if the unit's filled cells repeat a digit; a value above 9 fits no
+/// unit, so it counts as a conflict too
+fn repeats_digit(grid: &[[usize; 9]; 9], unit: &[(usize, usize); 9]) -> bool {
+    let mut seen = [false; 10];
+    for &(row, col) in unit {
+        let d = grid[row][col];
+        if d == 0 {
+            continue;
+        }
+        match seen.get_mut(d) {
+            Some(seen) if !*seen => *seen = true,
+            _ => return true,
+        }
+    }
+    false
+}
+
+/// True if any unit contains the same digit twice or a value above 9 (empty
+/// cells are ignored)
+pub fn has_conflicts(grid: &[[usize; 9]; 9]) -> bool {
+    UNITS.iter().any(|unit| repeats_digit(grid, unit))
+}
+
+/// Fraction of the 27 units with no repeated digit, empty cells counting as
+/// wildcards: 1.0 for any conflict-free partial grid
+pub fn satisfaction(grid: &[[usize; 9]; 9]) -> f64 {
+    let clean = units()
+        .iter()
+        .filter(|unit| !repeats_digit(grid, unit))
+        .count();
+    clean as f64 / units().len() as f64
+}
+
+/// Filled cells, in row-major order, whose digit also appears elsewhere in
+/// their row, column or box
+pub fn conflicting_cells(grid: &[[usize; 9]; 9]) -> Vec<(usize, usize)> {
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(row, col)| grid[row][col] != 0 && !can_place(grid, row, col, grid[row][col]))
+        .collect()
+}
+
+/// How many cells hold each value, indexed by value (0 counts empty cells);
+/// values above 9 are not counted
+pub fn digit_counts(grid: &[[usize; 9]; 9]) -> [usize; 10] {
+    let mut counts = [0; 10];
+    for &d in grid.iter().flatten() {
+        if let Some(count) = counts.get_mut(d) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+/// True if every cell holds a digit 1-9 and no unit repeats a digit
+pub fn is_solved(grid: &[[usize; 9]; 9]) -> bool {
+    // Nine of each digit is necessary, and much cheaper to check than the units
+    digit_counts(grid) == [0, 9, 9, 9, 9, 9, 9, 9, 9, 9] && !has_conflicts(grid)
+}
+
+/// What is wrong with the cell reported by `first_error`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+    Empty,
+    /// The digit also appears elsewhere in the cell's row, column or box
+    Duplicate,
+}
+
+/// First cell in row-major order that keeps the grid from being solved, or
+/// `None` if it is solved
+pub fn first_error(grid: &[[usize; 9]; 9]) -> Option<(usize, usize, ErrorKind)> {
+    (0..81).map(|i| (i / 9, i % 9)).find_map(|(row, col)| {
+        let d = grid[row][col];
+        if d == 0 {
+            Some((row, col, ErrorKind::Empty))
+        } else if !can_place(grid, row, col, d) {
+            Some((row, col, ErrorKind::Duplicate))
+        } else {
+            None
+        }
+    })
+}
+
+/// True if every given of `puzzle` is unchanged in `solution`
+pub fn respects_givens(puzzle: &[[usize; 9]; 9], solution: &[[usize; 9]; 9]) -> bool {
+    diff(puzzle, solution)
+        .iter()
+        .all(|&(_, _, given, _)| given == 0)
+}
+
+/// Whether digit d can go at (row, col) without repeating in its row, column or box
+pub fn can_place(grid: &[[usize; 9]; 9], row: usize, col: usize, d: usize) -> bool {
+    let cells = box_cells(box_index(row, col));
+    for (i, (r, c)) in cells.into_iter().enumerate() {
+        if (i != col && grid[row][i] == d)
+            || (i != row && grid[i][col] == d)
+            || ((r, c) != (row, col) && grid[r][c] == d)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// Panic unless every cell holds exactly one digit in 1-9; meant for tests
+/// checking that a solver returned a complete grid
+pub fn assert_single_digit_per_cell(grid: &[[usize; 9]; 9]) {
+    for (row, cells) in grid.iter().enumerate() {
+        for (col, &d) in cells.iter().enumerate() {
+            assert!(
+                (1..=9).contains(&d),
+                "cell ({}, {}) holds {}, expected a digit 1-9",
+                row,
+                col,
+                d
+            );
+        }
+    }
+}
+
+/// The grid turned a quarter turn clockwise
+pub fn rotate_cw<T: Copy + Default>(grid: &[[T; 9]; 9]) -> [[T; 9]; 9] {
+    let mut out = [[T::default(); 9]; 9];
+    for row in 0..9 {
+        for col in 0..9 {
+            out[col][8 - row] = grid[row][col];
+        }
+    }
+    out
+}
+
+/// The grid turned a quarter turn counter-clockwise
+pub fn rotate_ccw<T: Copy + Default>(grid: &[[T; 9]; 9]) -> [[T; 9]; 9] {
+    rotate_cw(&rotate_cw(&rotate_cw(grid)))
+}
+
+/// The grid mirrored along its main diagonal
+pub fn transpose<T: Copy + Default>(grid: &[[T; 9]; 9]) -> [[T; 9]; 9] {
+    let mut out = [[T::default(); 9]; 9];
+    for row in 0..9 {
+        for col in 0..9 {
+            out[col][row] = grid[row][col];
+        }
+    }
+    out
+}
+
+/// The grid mirrored left to right
+pub fn flip_horizontal<T: Copy>(grid: &[[T; 9]; 9]) -> [[T; 9]; 9] {
+    let mut out = *grid;
+    for row in out.iter_mut() {
+        row.reverse();
+    }
+    out
+}
+
+/// The grid mirrored top to bottom
+pub fn flip_vertical<T: Copy>(grid: &[[T; 9]; 9]) -> [[T; 9]; 9] {
+    let mut out = *grid;
+    out.reverse();
+    out
+}
+
+/// Renumber digits in order of first appearance (row-major), so the first
+/// digit seen becomes 1, the next new one 2, and so on
+fn relabel(grid: &[[usize; 9]; 9]) -> [[usize; 9]; 9] {
+    let mut map = [0; 10];
+    let mut next = 1;
+    let mut out = *grid;
+    for d in out.iter_mut().flatten().filter(|d| **d != 0) {
+        if map[*d] == 0 {
+            map[*d] = next;
+            next += 1;
+        }
+        *d = map[*d];
+    }
+    out
+}
+
+/// Representative shared by all grids equal up to rotation, reflection and
+/// digit relabelling: the smallest relabelled grid among the 8 symmetries.
+///
+/// Row, column, band and stack permutations are not considered, so some
+/// equivalent puzzles still get different forms.
+pub fn canonical_form(grid: &[[usize; 9]; 9]) -> [[usize; 9]; 9] {
+    let mut best = relabel(grid);
+    let mut turned = *grid;
+    for _ in 0..4 {
+        for candidate in [relabel(&turned), relabel(&transpose(&turned))] {
+            best = best.min(candidate);
+        }
+        turned = rotate_cw(&turned);
+    }
+    best
+}
+
+/// For each grid, the index of the first earlier grid with the same
+/// canonical form, if any
+pub fn find_duplicates(grids: &[[[usize; 9]; 9]]) -> Vec<Option<usize>> {
+    let mut first_seen = std::collections::HashMap::new();
+    grids
+        .iter()
+        .enumerate()
+        .map(|(i, grid)| {
+            let first = *first_seen.entry(canonical_form(grid)).or_insert(i);
+            (first != i).then_some(first)
+        })
+        .collect()
+}
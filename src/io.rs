@@ -0,0 +1,375 @@
+use std::fmt;
+use std::io::{self, BufRead, Read};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Box order outside the supported range (2 = 4×4 up to 4 = 16×16)
+    UnsupportedOrder(usize),
+    /// Character at `index` is not a valid label for this grid size
+    InvalidChar { index: usize, ch: char },
+    /// Wrong number of cells for the grid size
+    WrongLength { expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnsupportedOrder(order) => write!(f, "unsupported box order {}", order),
+            ParseError::InvalidChar { index, ch } => {
+                write!(f, "invalid character {:?} at index {}", ch, index)
+            }
+            ParseError::WrongLength { expected, found } => {
+                write!(f, "expected {} cells, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// Which characters a parser accepts besides the digit labels
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParseOptions {
+    /// `0` marks an empty cell (grids up to 9x9 only; 16x16 uses it as a label)
+    pub allow_zero: bool,
+    /// `.` marks an empty cell
+    pub allow_dot: bool,
+    /// Whitespace and `|`, `-`, `+` separators are skipped
+    pub allow_whitespace: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            allow_zero: true,
+            allow_dot: true,
+            allow_whitespace: true,
+        }
+    }
+}
+
+/// Parse a 9x9 puzzle from a single line of 81 cells (`.` or `0` for empty)
+pub fn parse_line(line: &str) -> Result<[[usize; 9]; 9], ParseError> {
+    parse_line_with(line, &ParseOptions::default())
+}
+
+/// Like `parse_line`, rejecting whatever `opts` disallows
+pub fn parse_line_with(line: &str, opts: &ParseOptions) -> Result<[[usize; 9]; 9], ParseError> {
+    let rows = parse_line_order_with(line, 3, opts)?;
+    let mut grid = [[0usize; 9]; 9];
+    for (r, row) in rows.iter().enumerate() {
+        grid[r].copy_from_slice(row);
+    }
+    Ok(grid)
+}
+
+/// Format a 9x9 grid as a single line of 81 cells, using `.` for empty
+pub fn to_line(grid: &[[usize; 9]; 9]) -> String {
+    let rows: Vec<Vec<usize>> = grid.iter().map(|row| row.to_vec()).collect();
+    to_line_order(&rows, 3)
+}
+
+/// Multi-line rendering with `|` and `-` box separators, one grid row per line
+pub fn to_pretty_string(grid: &[[usize; 9]; 9]) -> String {
+    let mut out = String::with_capacity(11 * 22);
+    for (row, cells) in grid.iter().enumerate() {
+        if row > 0 && row % 3 == 0 {
+            out.push_str("------+-------+------\n");
+        }
+        for (col, &v) in cells.iter().enumerate() {
+            if col > 0 {
+                out.push_str(if col % 3 == 0 { " | " } else { " " });
+            }
+            out.push(value_label(v, 3));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Pack a 9x9 grid into 41 bytes, one 4-bit nibble per cell.
+///
+/// Cells go in row-major order; cell `2k` is the low nibble of byte `k` and
+/// cell `2k + 1` the high nibble. The high nibble of the last byte is zero.
+pub fn pack(grid: &[[usize; 9]; 9]) -> [u8; 41] {
+    let mut out = [0u8; 41];
+    for (i, &v) in grid.iter().flatten().enumerate() {
+        debug_assert!(v <= 9, "cell value {} does not fit a nibble", v);
+        out[i / 2] |= ((v as u8) & 0x0f) << (4 * (i % 2));
+    }
+    out
+}
+
+/// Inverse of `pack`
+pub fn unpack(bytes: &[u8; 41]) -> [[usize; 9]; 9] {
+    let mut grid = [[0usize; 9]; 9];
+    for i in 0..81 {
+        grid[i / 9][i % 9] = ((bytes[i / 2] >> (4 * (i % 2))) & 0x0f) as usize;
+    }
+    grid
+}
+
+/// Lazily read puzzles from an `.sdm` stream (one 81-cell line per puzzle).
+///
+/// Blank lines and `#` comment lines are skipped. Each item carries its
+/// 1-based line number, and a bad line yields an error for that item only, so
+/// callers can keep going.
+pub fn sdm_puzzles<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = (usize, io::Result<[[usize; 9]; 9]>)> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            !matches!(line, Ok(l) if l.trim().is_empty() || l.trim_start().starts_with('#'))
+        })
+        .map(|(i, line)| {
+            let puzzle = line.and_then(|l| Ok(parse_line(l.trim())?));
+            (i + 1, puzzle)
+        })
+}
+
+/// A grid together with the metadata read alongside it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Puzzle {
+    pub name: Option<String>,
+    pub grid: [[usize; 9]; 9],
+}
+
+impl From<[[usize; 9]; 9]> for Puzzle {
+    fn from(grid: [[usize; 9]; 9]) -> Self {
+        Puzzle { name: None, grid }
+    }
+}
+
+/// Split leading `#` comment lines from the rest of a file. A `# Name: ...`
+/// comment gives the name; other comments are ignored.
+fn split_header(text: &str) -> (Option<String>, &str) {
+    let mut name = None;
+    let mut rest = text;
+    while let Some(comment) = rest.trim_start().strip_prefix('#') {
+        let (line, tail) = comment.split_once('\n').unwrap_or((comment, ""));
+        if let Some(n) = line.trim().strip_prefix("Name:") {
+            name = Some(n.trim().to_string());
+        }
+        rest = tail;
+    }
+    (name, rest)
+}
+
+/// Puzzles in a file's text: either a single grid (one line or a 9x9 block,
+/// separators allowed), an `.sdm` collection with one puzzle per line, or an
+/// OpenSudoku XML collection.
+///
+/// Leading `#` lines are comments; `# Name: ...` names a single grid, or every
+/// puzzle of a collection.
+pub fn parse_puzzles(text: &str) -> io::Result<Vec<Puzzle>> {
+    if text.trim_start().starts_with('<') {
+        return read_opensudoku(text.as_bytes());
+    }
+    let (name, body) = split_header(text);
+    match parse_line(body) {
+        Ok(grid) => Ok(vec![Puzzle { name, grid }]),
+        Err(_) => Ok(read_sdm(body.as_bytes())?
+            .into_iter()
+            .map(|grid| Puzzle {
+                name: name.clone(),
+                grid,
+            })
+            .collect()),
+    }
+}
+
+/// Read every puzzle of an `.sdm` stream, failing on the first bad line
+pub fn read_sdm<R: BufRead>(reader: R) -> io::Result<Vec<[[usize; 9]; 9]>> {
+    sdm_puzzles(reader)
+        .map(|(line, puzzle)| {
+            puzzle.map_err(|e| io::Error::new(e.kind(), format!("line {}: {}", line, e)))
+        })
+        .collect()
+}
+
+/// Read an OpenSudoku XML collection: each `<game>` element's `data` attribute
+/// is one 81-cell puzzle, named by the element's `name` attribute if it has one.
+///
+/// This scans for `<game` tags rather than parsing the XML, so comments and
+/// CDATA are not understood.
+pub fn read_opensudoku<R: Read>(mut reader: R) -> io::Result<Vec<Puzzle>> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let mut puzzles = Vec::new();
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("<game") {
+        let tag = &rest[start + "<game".len()..];
+        let end = tag
+            .find('>')
+            .ok_or_else(|| invalid("unterminated <game> tag".into()))?;
+        let attrs = &tag[..end];
+        rest = &tag[end..];
+        // Some other element whose name starts with `game`
+        if !attrs.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let game = puzzles.len() + 1;
+        let data = xml_attr(attrs, "data")
+            .ok_or_else(|| invalid(format!("game {}: no data attribute", game)))?;
+        let grid = parse_line(data).map_err(|e| invalid(format!("game {}: {}", game, e)))?;
+        let name = xml_attr(attrs, "name").map(xml_unescape);
+        puzzles.push(Puzzle { name, grid });
+    }
+    Ok(puzzles)
+}
+
+/// Value of attribute `name` in the inside of a start tag, without its quotes
+fn xml_attr<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attrs;
+    while let Some(i) = rest.find(name) {
+        let whole_word = rest[..i].ends_with(char::is_whitespace);
+        let after = &rest[i + name.len()..];
+        if whole_word && let Some(value) = after.trim_start().strip_prefix('=') {
+            let value = value.trim_start();
+            let quote = value.chars().next().filter(|&q| q == '"' || q == '\'')?;
+            let value = &value[1..];
+            return value.find(quote).map(|end| &value[..end]);
+        }
+        rest = after;
+    }
+    None
+}
+
+/// Decode the predefined XML entities
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Cheap hashable key for a 9x9 grid, backed by its 81-character line
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GridKey(String);
+
+impl GridKey {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Parse the key back into a grid
+    pub fn to_grid(&self) -> Result<[[usize; 9]; 9], ParseError> {
+        parse_line(&self.0)
+    }
+}
+
+impl From<[[usize; 9]; 9]> for GridKey {
+    fn from(grid: [[usize; 9]; 9]) -> Self {
+        GridKey(to_line(&grid))
+    }
+}
+
+impl From<&[[usize; 9]; 9]> for GridKey {
+    fn from(grid: &[[usize; 9]; 9]) -> Self {
+        GridKey(to_line(grid))
+    }
+}
+
+impl fmt::Display for GridKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Parse a grid with the given box order (side length `order * order`).
+///
+/// Up to 9x9 cells are labelled `1-9`; 16x16 uses hex labels `0-F` for the
+/// values 1-16. `.` is always empty, and `0` is also empty when it isn't a label.
+/// Whitespace and `|`, `-`, `+` row/box separators are skipped.
+pub fn parse_line_order(line: &str, order: usize) -> Result<Vec<Vec<usize>>, ParseError> {
+    parse_line_order_with(line, order, &ParseOptions::default())
+}
+
+/// Like `parse_line_order`, rejecting whatever `opts` disallows. Errors point at
+/// the character index of the first offending character.
+pub fn parse_line_order_with(
+    line: &str,
+    order: usize,
+    opts: &ParseOptions,
+) -> Result<Vec<Vec<usize>>, ParseError> {
+    check_order(order)?;
+    let side = order * order;
+    let mut cells = Vec::with_capacity(side * side);
+    for (index, ch) in line.chars().enumerate() {
+        let layout = ch.is_whitespace() || matches!(ch, '|' | '-' | '+');
+        let rejected = (layout && !opts.allow_whitespace)
+            || (ch == '.' && !opts.allow_dot)
+            || (ch == '0' && side <= 9 && !opts.allow_zero);
+        if rejected {
+            return Err(ParseError::InvalidChar { index, ch });
+        }
+        if layout {
+            continue;
+        }
+        match label_value(ch, order) {
+            Some(v) => cells.push(v),
+            None => return Err(ParseError::InvalidChar { index, ch }),
+        }
+    }
+    if cells.len() != side * side {
+        return Err(ParseError::WrongLength {
+            expected: side * side,
+            found: cells.len(),
+        });
+    }
+    Ok(cells.chunks(side).map(|row| row.to_vec()).collect())
+}
+
+/// Format a grid with the given box order as a single line, using `.` for empty
+pub fn to_line_order(grid: &[Vec<usize>], order: usize) -> String {
+    grid.iter()
+        .flat_map(|row| row.iter().map(|&v| value_label(v, order)))
+        .collect()
+}
+
+fn check_order(order: usize) -> Result<(), ParseError> {
+    if (2..=4).contains(&order) {
+        Ok(())
+    } else {
+        Err(ParseError::UnsupportedOrder(order))
+    }
+}
+
+/// Cell value for a label, 0 meaning empty
+fn label_value(ch: char, order: usize) -> Option<usize> {
+    let side = order * order;
+    if ch == '.' {
+        return Some(0);
+    }
+    if side <= 9 {
+        let v = ch.to_digit(10)? as usize;
+        return (v <= side).then_some(v);
+    }
+    // Hex labels are zero-based, so '0' is the value 1
+    let v = ch.to_digit(16)? as usize + 1;
+    (v <= side).then_some(v)
+}
+
+/// Label for a cell value, `.` for empty
+pub fn value_label(v: usize, order: usize) -> char {
+    if v == 0 {
+        '.'
+    } else if order * order <= 9 {
+        char::from_digit(v as u32, 10).unwrap_or('?')
+    } else {
+        char::from_digit(v as u32 - 1, 16)
+            .map(|c| c.to_ascii_uppercase())
+            .unwrap_or('?')
+    }
+}
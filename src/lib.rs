@@ -0,0 +1,9 @@
+pub mod export;
+pub mod generator;
+pub mod grid;
+pub mod io;
+pub mod puzzles;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod solver;
+pub mod tui;
@@ -1,6 +1,35 @@
-mod solver;
-mod tui;
+mod cli;
+
+use std::io::{self, IsTerminal};
+
+use sudoku_sat::tui;
 
 fn main() -> std::io::Result<()> {
-    tui::run()
+    let opts = match cli::Options::parse(std::env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let result = if opts.is_batch() {
+        cli::run(&opts)
+    } else if opts.tui
+        || opts.sample.is_some()
+        || !io::stdin().is_terminal()
+        || std::env::var_os(cli::PUZZLE_ENV).is_some()
+    {
+        // Piped input and the env var are puzzle sources like `--file`;
+        // `load_puzzles` picks the one with the highest precedence
+        cli::load_puzzles(&opts).and_then(tui::run)
+    } else {
+        // A bare run opens an empty grid
+        tui::run(Vec::new())
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    Ok(())
 }
@@ -0,0 +1,36 @@
+use crate::io::parse_line;
+use crate::solver::logic::Difficulty;
+
+/// One sample puzzle per rating, each rated so by `estimate_difficulty`
+const SAMPLES: [(Difficulty, &str); 4] = [
+    (
+        Difficulty::Easy,
+        ".36...9..1..53.2....4.....6.47....53.....8.6969..4.......8.7..1..2.....4.85....2.",
+    ),
+    (
+        Difficulty::Medium,
+        "9....8..23159......8..3...6.....37.9..321..854..79..3...2......14.3.....8..1...23",
+    ),
+    (
+        Difficulty::Hard,
+        "9..73..5.8.........3.1.2...56......9.1......4..29.15....5..48...86....42...2...7.",
+    ),
+    (
+        Difficulty::Expert,
+        ".8....3.5.......7.5.3.2.86..4.8......1..794.8......1.98..73......1.....32...1....",
+    ),
+];
+
+/// Sample puzzle loaded by the TUI `d` key; the easy sample
+pub fn default_puzzle() -> [[usize; 9]; 9] {
+    sample_puzzle(Difficulty::Easy)
+}
+
+/// The built-in sample puzzle of the given rating
+pub fn sample_puzzle(difficulty: Difficulty) -> [[usize; 9]; 9] {
+    let (_, line) = SAMPLES
+        .iter()
+        .find(|(d, _)| *d == difficulty)
+        .expect("a sample for every difficulty");
+    parse_line(line).expect("sample puzzles are valid lines")
+}
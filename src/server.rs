@@ -0,0 +1,128 @@
+//! Minimal single-threaded HTTP front-end for the solvers.
+//!
+//! `POST /solve` with an 81-character puzzle line as the body (`.` or `0` for
+//! empty cells). An optional `?solver=sat|backtracking|auto|exact-cover` query
+//! picks the backend.
+//! The response is JSON:
+//!
+//! ```text
+//! {"solved": true, "grid": "<81 chars>", "time_ms": 3}
+//! ```
+//!
+//! `grid` is `null` when the puzzle has no solution. Malformed puzzles get a
+//! 400 with `{"error": "..."}`.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use crate::io::{parse_line, to_line};
+use crate::solver::{SolveError, SolverKind, SudokuSolver, make_solver};
+
+/// Largest request body accepted; a puzzle line is far smaller
+const MAX_BODY: usize = 4096;
+
+/// How long a read may block, so a client that stalls mid-request can't hold
+/// up the single-threaded accept loop
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn serve(port: u16) -> io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    eprintln!("Listening on http://127.0.0.1:{}", port);
+    for stream in listener.incoming() {
+        // A broken client connection shouldn't stop the server
+        if let Err(e) = stream.and_then(handle_connection) {
+            eprintln!("Connection error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("");
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (status, body) = if path != "/solve" {
+        ("404 Not Found", error_json("not found"))
+    } else if method != "POST" {
+        ("405 Method Not Allowed", error_json("use POST"))
+    } else if content_length > MAX_BODY {
+        (
+            "413 Payload Too Large",
+            error_json("request body too large"),
+        )
+    } else {
+        let mut buf = vec![0; content_length];
+        reader.read_exact(&mut buf)?;
+        solve_request(&String::from_utf8_lossy(&buf), query)
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn solve_request(body: &str, query: &str) -> (&'static str, String) {
+    let mut kind = SolverKind::Sat;
+    for pair in query.split('&') {
+        if let Some(("solver", name)) = pair.split_once('=') {
+            match SolverKind::from_name(name) {
+                Some(k) => kind = k,
+                None => return ("400 Bad Request", error_json("unknown solver")),
+            }
+        }
+    }
+
+    let puzzle = match parse_line(body.trim()) {
+        Ok(p) => p,
+        Err(e) => return ("400 Bad Request", error_json(&e.to_string())),
+    };
+
+    let start = Instant::now();
+    let solution = make_solver(kind).solve(&puzzle);
+    let time_ms = start.elapsed().as_millis();
+
+    let json = match solution {
+        Ok(grid) => format!(
+            r#"{{"solved": true, "grid": "{}", "time_ms": {}}}"#,
+            to_line(&grid),
+            time_ms
+        ),
+        Err(SolveError::Unsatisfiable) => format!(
+            r#"{{"solved": false, "grid": null, "time_ms": {}}}"#,
+            time_ms
+        ),
+        Err(e) => return ("500 Internal Server Error", error_json(&e.to_string())),
+    };
+    ("200 OK", json)
+}
+
+fn error_json(msg: &str) -> String {
+    let escaped = msg.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(r#"{{"error": "{}"}}"#, escaped)
+}
@@ -0,0 +1,134 @@
+use super::backtracking::BacktrackingSudokuSolver;
+use super::logic::solve_logic_only;
+use super::sat::SatSudokuSolver;
+use super::{SolveError, SudokuSolver, Variant, panic_message, solve_complete};
+use crate::grid::has_conflicts;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+/// Nodes the backtracking stage may visit before handing over to SAT; easy
+/// and medium puzzles finish well within this
+pub const AUTO_BACKTRACK_NODES: usize = 100_000;
+
+/// Callback told why a solve fell back from SAT to plain backtracking
+pub type FallbackHook = Box<dyn FnMut(&str)>;
+
+/// Runs the cheapest solver that can finish the puzzle, so callers don't
+/// need to pick a backend. See `solve_auto` for the pipeline.
+#[derive(Default)]
+pub struct AutoSudokuSolver {
+    variant: Variant,
+    /// Node budget of the backtracking stage; `None` means `AUTO_BACKTRACK_NODES`
+    max_nodes: Option<usize>,
+    /// Solver for the last stage; `None` is a default `SatSudokuSolver`
+    sat: Option<Box<dyn SudokuSolver>>,
+    /// Timeout of the default SAT stage
+    timeout: Option<Duration>,
+    /// Why the last solve fell back from SAT to plain backtracking, if it did
+    fallback: Option<String>,
+    on_fallback: Option<FallbackHook>,
+}
+
+impl AutoSudokuSolver {
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Let the backtracking stage visit `limit` nodes before handing over to
+    /// SAT, instead of `AUTO_BACKTRACK_NODES`
+    pub fn with_max_nodes(mut self, limit: usize) -> Self {
+        self.max_nodes = Some(limit);
+        self
+    }
+
+    /// Stop the SAT stage after `timeout`, failing with
+    /// `SolveError::Interrupted`. A solver given to `with_sat_solver` keeps
+    /// its own settings.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Run `solver` as the SAT stage, e.g. one configured with a timeout.
+    /// It should use the same variant as this solver.
+    pub fn with_sat_solver(mut self, solver: impl SudokuSolver + 'static) -> Self {
+        self.sat = Some(Box::new(solver));
+        self
+    }
+
+    /// Report each fallback from SAT to backtracking to `hook`, with the same
+    /// reason `fallback_reason` returns
+    pub fn with_fallback_hook(mut self, hook: FallbackHook) -> Self {
+        self.on_fallback = Some(hook);
+        self
+    }
+
+    /// The SAT backend failure that made the last solve fall back to
+    /// backtracking, or `None` if it didn't
+    pub fn fallback_reason(&self) -> Option<&str> {
+        self.fallback.as_deref()
+    }
+}
+
+impl SudokuSolver for AutoSudokuSolver {
+    fn solve(&mut self, puzzle: &[[usize; 9]; 9]) -> Result<[[usize; 9]; 9], SolveError> {
+        self.fallback = None;
+        if let Some(result) = solve_complete(puzzle, self.variant) {
+            return result;
+        }
+        // Stage 1: logic, which only knows the classic units
+        if self.variant == Variant::Classic {
+            if has_conflicts(puzzle) {
+                return Err(SolveError::Unsatisfiable);
+            }
+            if let Some(solution) = solve_logic_only(puzzle) {
+                return Ok(solution);
+            }
+        }
+        // Stage 2: bounded backtracking; a finished search is final either way
+        let mut backtracking = BacktrackingSudokuSolver::default()
+            .with_variant(self.variant)
+            .with_max_nodes(self.max_nodes.unwrap_or(AUTO_BACKTRACK_NODES));
+        match backtracking.solve(puzzle) {
+            Err(SolveError::NodeLimitExceeded { .. }) => {}
+            result => return result,
+        }
+        // Stage 3: SAT for whatever is left. If the backend errors or panics,
+        // unbounded backtracking still gives an answer, just more slowly
+        let (variant, timeout) = (self.variant, self.timeout);
+        let sat = self.sat.get_or_insert_with(|| {
+            let sat = SatSudokuSolver::default().with_variant(variant);
+            Box::new(match timeout {
+                Some(timeout) => sat.with_timeout(timeout),
+                None => sat,
+            })
+        });
+        let reason = match panic::catch_unwind(AssertUnwindSafe(|| sat.solve(puzzle))) {
+            Ok(Err(SolveError::SolverBackendError(msg))) => msg,
+            Ok(result) => return result,
+            Err(payload) => format!("backend panicked: {}", panic_message(&*payload)),
+        };
+        if let Some(hook) = self.on_fallback.as_mut() {
+            hook(&reason);
+        }
+        self.fallback = Some(reason);
+        BacktrackingSudokuSolver::default()
+            .with_variant(self.variant)
+            .solve(puzzle)
+    }
+}
+
+/// Solve a classic puzzle with the fastest stage that succeeds:
+///
+/// 1. the technique library (`logic::solve_logic_only`), which settles most
+///    published puzzles without any search;
+/// 2. backtracking, branching on the cell with the fewest candidates (kept
+///    as per-unit bitmasks), limited to `AUTO_BACKTRACK_NODES` nodes;
+/// 3. the SAT solver, which has no search budget; should the backend fail,
+///    unbounded backtracking is used instead.
+///
+/// Each stage is a public solver of its own and can be run on its own.
+pub fn solve_auto(puzzle: &[[usize; 9]; 9]) -> Result<[[usize; 9]; 9], SolveError> {
+    AutoSudokuSolver::default().solve(puzzle)
+}
@@ -0,0 +1,83 @@
+use super::{SolverKind, SudokuSolver, make_solver};
+use crate::generator::{Rng, generate};
+use crate::grid::{is_solved, respects_givens};
+use crate::io::to_line;
+
+/// Generate `count` random puzzles from `seed` and solve each with every solver.
+///
+/// Each output must be a valid solution that keeps the givens, and all solvers
+/// must agree. The error message carries the seed and puzzle line to reproduce.
+pub fn round_trip(seed: u64, count: usize, clues: usize) -> Result<(), String> {
+    let mut rng = Rng::new(seed);
+    for i in 0..count {
+        let puzzle = generate(clues, &mut rng);
+        let fail = |msg: String| {
+            Err(format!(
+                "seed {} puzzle {} ({}): {}",
+                seed,
+                i,
+                to_line(&puzzle),
+                msg
+            ))
+        };
+
+        let mut first: Option<[[usize; 9]; 9]> = None;
+        for kind in SolverKind::ALL {
+            let solution = match make_solver(kind).solve(&puzzle) {
+                Ok(solution) => solution,
+                Err(e) => return fail(format!("{:?} failed: {}", kind, e)),
+            };
+            if !is_solved(&solution) {
+                return fail(format!(
+                    "{:?} returned invalid grid {}",
+                    kind,
+                    to_line(&solution)
+                ));
+            }
+            if !respects_givens(&puzzle, &solution) {
+                return fail(format!("{:?} changed a given", kind));
+            }
+            match first {
+                Some(expected) if expected != solution => {
+                    return fail(format!(
+                        "{:?} disagrees: {} vs {}",
+                        kind,
+                        to_line(&solution),
+                        to_line(&expected)
+                    ));
+                }
+                _ => first = Some(solution),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Solve `puzzle` `runs` times with a fresh instance of every solver and check
+/// each solver returns the same grid every time.
+///
+/// Solvers are single-threaded and seedless, so repeated runs must agree even
+/// on puzzles with several solutions; this catches anything that changes that.
+pub fn determinism(puzzle: &[[usize; 9]; 9], runs: usize) -> Result<(), String> {
+    for kind in SolverKind::ALL {
+        let mut first: Option<[[usize; 9]; 9]> = None;
+        for run in 0..runs {
+            let solution = make_solver(kind)
+                .solve(puzzle)
+                .map_err(|e| format!("{:?} run {} failed: {}", kind, run, e))?;
+            match first {
+                Some(expected) if expected != solution => {
+                    return Err(format!(
+                        "{:?} run {} returned {} but run 0 returned {}",
+                        kind,
+                        run,
+                        to_line(&solution),
+                        to_line(&expected)
+                    ));
+                }
+                _ => first = Some(solution),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,261 @@
+use super::{SolveError, SudokuSolver, Variant, solve_complete};
+use crate::grid::box_index;
+
+/// Sudoku as an exact cover problem, solved with Knuth's Algorithm X over
+/// dancing links.
+///
+/// Each `(row, col, digit)` placement is a row of the cover matrix covering
+/// four constraints: the cell is filled, and the digit appears in the row,
+/// the column and the box. The box constraints are left out for variants
+/// without boxes.
+#[derive(Default)]
+pub struct ExactCoverSudokuSolver {
+    variant: Variant,
+    /// Give up once the search has entered this many nodes; `None` is unbounded
+    max_nodes: Option<usize>,
+}
+
+impl ExactCoverSudokuSolver {
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Bound the search so adversarial puzzles fail with
+    /// `SolveError::NodeLimitExceeded` instead of running for minutes
+    pub fn with_max_nodes(mut self, limit: usize) -> Self {
+        self.max_nodes = Some(limit);
+        self
+    }
+}
+
+impl SudokuSolver for ExactCoverSudokuSolver {
+    fn solve(&mut self, puzzle: &[[usize; 9]; 9]) -> Result<[[usize; 9]; 9], SolveError> {
+        if let Some(result) = solve_complete(puzzle, self.variant) {
+            return result;
+        }
+        let mut links = Links::for_sudoku(self.variant);
+        // The givens are chosen up front; one that clashes with an earlier
+        // given finds a constraint already covered
+        for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+            let d = puzzle[row][col];
+            if d != 0 && !links.select(links.first[placement(row, col, d)]) {
+                return Err(SolveError::Unsatisfiable);
+            }
+        }
+        let mut search = Search {
+            chosen: Vec::new(),
+            nodes: 0,
+            max_nodes: self.max_nodes,
+        };
+        if !links.search(&mut search)? {
+            return Err(SolveError::Unsatisfiable);
+        }
+        let mut grid = *puzzle;
+        for node in search.chosen {
+            let (row, col, d) = links.candidate[node];
+            grid[row][col] = d;
+        }
+        Ok(grid)
+    }
+}
+
+/// Index of the `(row, col, digit)` placement among the 729 candidates
+fn placement(row: usize, col: usize, d: usize) -> usize {
+    (row * 9 + col) * 9 + d - 1
+}
+
+/// The constraint columns (0-indexed, headers excluded) that placing `d` at
+/// `(row, col)` covers
+fn constraints(row: usize, col: usize, d: usize, variant: Variant) -> Vec<usize> {
+    let mut cols = vec![row * 9 + col, 81 + row * 9 + d - 1, 162 + col * 9 + d - 1];
+    if variant.has_boxes() {
+        cols.push(243 + box_index(row, col) * 9 + d - 1);
+    }
+    cols
+}
+
+/// The header every column header hangs off
+const ROOT: usize = 0;
+
+/// Sparse cover matrix as circular doubly linked lists. Nodes `1..=columns`
+/// are the column headers; the rest are the matrix's ones.
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Column header of every node
+    column: Vec<usize>,
+    /// Nodes still linked into each column
+    size: Vec<usize>,
+    /// Placement each node belongs to; unused for headers
+    candidate: Vec<(usize, usize, usize)>,
+    /// First node of each placement's row, indexed by `placement`
+    first: Vec<usize>,
+}
+
+/// Per-solve search state
+struct Search {
+    /// Nodes of the rows picked so far, one per placement
+    chosen: Vec<usize>,
+    nodes: usize,
+    max_nodes: Option<usize>,
+}
+
+impl Links {
+    /// The full 729-row matrix for `variant`
+    fn for_sudoku(variant: Variant) -> Self {
+        let columns = if variant.has_boxes() { 324 } else { 243 };
+        let mut links = Links {
+            left: (0..=columns)
+                .map(|i| (i + columns) % (columns + 1))
+                .collect(),
+            right: (0..=columns).map(|i| (i + 1) % (columns + 1)).collect(),
+            up: (0..=columns).collect(),
+            down: (0..=columns).collect(),
+            column: (0..=columns).collect(),
+            size: vec![0; columns + 1],
+            candidate: vec![(0, 0, 0); columns + 1],
+            first: Vec::with_capacity(729),
+        };
+        for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+            for d in 1..=9 {
+                links.add_row((row, col, d), &constraints(row, col, d, variant));
+            }
+        }
+        links
+    }
+
+    /// Append a row with ones in the given (0-indexed) columns
+    fn add_row(&mut self, candidate: (usize, usize, usize), columns: &[usize]) {
+        let first = self.column.len();
+        for (i, &c) in columns.iter().enumerate() {
+            let header = c + 1;
+            let node = first + i;
+            self.column.push(header);
+            self.candidate.push(candidate);
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+            // Circular within the row
+            let (prev, next) = (i + columns.len() - 1, i + 1);
+            self.left.push(first + prev % columns.len());
+            self.right.push(first + next % columns.len());
+        }
+        self.first.push(first);
+    }
+
+    /// Unlink column `c` and every row that has a one in it
+    fn cover(&mut self, c: usize) {
+        self.right[self.left[c]] = self.right[c];
+        self.left[self.right[c]] = self.left[c];
+        let mut i = self.down[c];
+        while i != c {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    /// Undo `cover(c)`, relinking in the reverse order
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.up[c];
+        while i != c {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        self.right[self.left[c]] = c;
+        self.left[self.right[c]] = c;
+    }
+
+    /// Whether column header `c` is still linked in
+    fn is_open(&self, c: usize) -> bool {
+        self.right[self.left[c]] == c
+    }
+
+    /// Pick the row containing `node` for good, covering its columns. False
+    /// (with nothing changed) if one of them is already covered.
+    fn select(&mut self, node: usize) -> bool {
+        let mut j = node;
+        loop {
+            if !self.is_open(self.column[j]) {
+                return false;
+            }
+            j = self.right[j];
+            if j == node {
+                break;
+            }
+        }
+        loop {
+            self.cover(self.column[j]);
+            j = self.right[j];
+            if j == node {
+                return true;
+            }
+        }
+    }
+
+    /// Algorithm X: branch on the open column with the fewest rows, trying
+    /// its rows top to bottom (ascending digits). Leaves the matrix covered
+    /// on success, since the caller only reads `search.chosen`.
+    fn search(&mut self, search: &mut Search) -> Result<bool, SolveError> {
+        search.nodes += 1;
+        if let Some(limit) = search.max_nodes
+            && search.nodes > limit
+        {
+            return Err(SolveError::NodeLimitExceeded { limit });
+        }
+        if self.right[ROOT] == ROOT {
+            return Ok(true);
+        }
+        let mut c = self.right[ROOT];
+        let mut j = self.right[c];
+        while j != ROOT {
+            if self.size[j] < self.size[c] {
+                c = j;
+            }
+            j = self.right[j];
+        }
+        if self.size[c] == 0 {
+            return Ok(false);
+        }
+
+        self.cover(c);
+        let mut r = self.down[c];
+        while r != c {
+            search.chosen.push(r);
+            let mut j = self.right[r];
+            while j != r {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+            if self.search(search)? {
+                return Ok(true);
+            }
+            let mut j = self.left[r];
+            while j != r {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            search.chosen.pop();
+            r = self.down[r];
+        }
+        self.uncover(c);
+        Ok(false)
+    }
+}
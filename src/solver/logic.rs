@@ -0,0 +1,671 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::grid::{UNITS, box_cells, box_index, has_conflicts};
+use crate::io::GridKey;
+
+/// Digits still possible in each cell, as bitmasks (bit `d` set = digit `d` allowed).
+/// Filled cells have no candidates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Candidates {
+    cells: [[u16; 9]; 9],
+}
+
+const ALL_DIGITS: u16 = 0b11_1111_1110;
+
+/// Bits of the digits already in the cell's row, column and box
+fn peer_digits(grid: &[[usize; 9]; 9], row: usize, col: usize) -> u16 {
+    let (br, bc) = box_cells(box_index(row, col))[0];
+    let mut used = 0;
+    for i in 0..9 {
+        used |= 1 << grid[row][i];
+        used |= 1 << grid[i][col];
+        used |= 1 << grid[br + i / 3][bc + i % 3];
+    }
+    used
+}
+
+impl Candidates {
+    pub fn from_grid(grid: &[[usize; 9]; 9]) -> Self {
+        let mut cands = Candidates {
+            cells: [[ALL_DIGITS; 9]; 9],
+        };
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, &d) in cells.iter().enumerate() {
+                if d != 0 {
+                    cands.place(row, col, d);
+                }
+            }
+        }
+        cands
+    }
+
+    pub fn mask(&self, row: usize, col: usize) -> u16 {
+        self.cells[row][col]
+    }
+
+    pub fn contains(&self, row: usize, col: usize, d: usize) -> bool {
+        self.cells[row][col] & (1 << d) != 0
+    }
+
+    pub fn count(&self, row: usize, col: usize) -> usize {
+        self.cells[row][col].count_ones() as usize
+    }
+
+    /// Candidate digits of a cell in ascending order
+    pub fn digits(&self, row: usize, col: usize) -> Vec<usize> {
+        (1..=9).filter(|&d| self.contains(row, col, d)).collect()
+    }
+
+    /// Remove a candidate; returns whether it was present
+    pub fn remove(&mut self, row: usize, col: usize, d: usize) -> bool {
+        let had = self.contains(row, col, d);
+        self.cells[row][col] &= !(1 << d);
+        had
+    }
+
+    /// Bring the cell and its row, column and box peers up to date after
+    /// `grid[row][col]` changed, whether a digit was placed or cleared; the
+    /// rest of the grid keeps its candidates
+    pub fn refresh(&mut self, grid: &[[usize; 9]; 9], row: usize, col: usize) {
+        let (br, bc) = box_cells(box_index(row, col))[0];
+        let affected = (0..9)
+            .map(|i| (row, i))
+            .chain((0..9).map(|i| (i, col)))
+            .chain((0..9).map(|i| (br + i / 3, bc + i % 3)));
+        for (r, c) in affected {
+            self.cells[r][c] = if grid[r][c] != 0 {
+                0
+            } else {
+                ALL_DIGITS & !peer_digits(grid, r, c)
+            };
+        }
+    }
+
+    /// Record a placement: the cell has no candidates left and `d` is
+    /// removed from every peer in its row, column and box
+    pub fn place(&mut self, row: usize, col: usize, d: usize) {
+        self.cells[row][col] = 0;
+        let cells = box_cells(box_index(row, col));
+        for (i, (r, c)) in cells.into_iter().enumerate() {
+            self.remove(row, i, d);
+            self.remove(i, col, d);
+            self.remove(r, c, d);
+        }
+    }
+}
+
+/// A row, column or box, 0-indexed
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Unit {
+    Row(usize),
+    Col(usize),
+    Box(usize),
+}
+
+impl Unit {
+    /// Unit for an index into `grid::UNITS`
+    pub fn from_index(i: usize) -> Self {
+        match i {
+            0..=8 => Unit::Row(i),
+            9..=17 => Unit::Col(i - 9),
+            _ => Unit::Box(i - 18),
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unit::Row(i) => write!(f, "row {}", i + 1),
+            Unit::Col(i) => write!(f, "column {}", i + 1),
+            Unit::Box(i) => write!(f, "box {}", i + 1),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Technique {
+    /// The cell has a single candidate left
+    NakedSingle,
+    /// The digit fits in only one cell of the unit
+    HiddenSingle(Unit),
+    /// In two rows (or columns), `digit` fits only in the same two columns
+    /// (or rows), so it is ruled out of the rest of those
+    XWing { digit: usize, lines: [Unit; 2] },
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Technique::NakedSingle => write!(f, "Naked single"),
+            Technique::HiddenSingle(unit) => write!(f, "Hidden single in {}", unit),
+            Technique::XWing { digit, lines } => {
+                write!(f, "X-Wing on {} in {} and {}", digit, lines[0], lines[1])
+            }
+        }
+    }
+}
+
+/// One deduction made by the logical solver
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SolveStep {
+    Place {
+        row: usize,
+        col: usize,
+        digit: usize,
+        technique: Technique,
+    },
+    /// Candidates ruled out of a cell without placing anything
+    Eliminate {
+        cell: (usize, usize),
+        digits: Vec<usize>,
+        technique: Technique,
+    },
+    /// No technique applies; the remaining cells need guessing
+    GuessRequired { empty: usize },
+}
+
+impl fmt::Display for SolveStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveStep::Place {
+                row,
+                col,
+                digit,
+                technique,
+            } => {
+                let cell = format!("R{}C{}={}", row + 1, col + 1, digit);
+                match technique {
+                    Technique::NakedSingle => write!(f, "Naked single {}", cell),
+                    technique => write!(f, "{}: {}", technique, cell),
+                }
+            }
+            SolveStep::Eliminate {
+                cell: (row, col),
+                digits,
+                technique,
+            } => {
+                let digits: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+                write!(
+                    f,
+                    "{}: R{}C{} can't be {}",
+                    technique,
+                    row + 1,
+                    col + 1,
+                    digits.join(" or ")
+                )
+            }
+            SolveStep::GuessRequired { empty } => {
+                write!(f, "Guessing required ({} cells left)", empty)
+            }
+        }
+    }
+}
+
+/// Cell with exactly one candidate
+pub fn find_naked_single(
+    grid: &[[usize; 9]; 9],
+    cands: &Candidates,
+) -> Option<(usize, usize, usize)> {
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .find(|&(row, col)| grid[row][col] == 0 && cands.count(row, col) == 1)
+        .map(|(row, col)| (row, col, cands.mask(row, col).trailing_zeros() as usize))
+}
+
+/// Digit with exactly one possible cell in some unit
+pub fn find_hidden_single(
+    grid: &[[usize; 9]; 9],
+    cands: &Candidates,
+) -> Option<(usize, usize, usize, Unit)> {
+    for (i, unit) in UNITS.iter().enumerate() {
+        for d in 1..=9 {
+            let mut spots = unit
+                .iter()
+                .filter(|&&(r, c)| grid[r][c] == 0 && cands.contains(r, c, d));
+            if let (Some(&(r, c)), None) = (spots.next(), spots.next()) {
+                return Some((r, c, d, Unit::from_index(i)));
+            }
+        }
+    }
+    None
+}
+
+/// First cell an X-Wing rules a candidate out of. Rows are tried as the base
+/// lines before columns; only cells still holding the digit are reported, so
+/// applying each step in turn always makes progress.
+pub fn find_x_wing(grid: &[[usize; 9]; 9], cands: &Candidates) -> Option<SolveStep> {
+    for by_rows in [true, false] {
+        let cell = |line: usize, pos: usize| if by_rows { (line, pos) } else { (pos, line) };
+        let unit = |line: usize| {
+            if by_rows {
+                Unit::Row(line)
+            } else {
+                Unit::Col(line)
+            }
+        };
+        for digit in 1..=9 {
+            // Bit `pos` set: the digit can go at that position of the line
+            let spots: [u16; 9] = std::array::from_fn(|line| {
+                (0..9)
+                    .filter(|&pos| {
+                        let (r, c) = cell(line, pos);
+                        grid[r][c] == 0 && cands.contains(r, c, digit)
+                    })
+                    .fold(0, |mask, pos| mask | (1 << pos))
+            });
+            for a in 0..9 {
+                if spots[a].count_ones() != 2 {
+                    continue;
+                }
+                for b in a + 1..9 {
+                    if spots[b] != spots[a] {
+                        continue;
+                    }
+                    let victim = (0..9)
+                        .filter(|&line| line != a && line != b)
+                        .flat_map(|line| {
+                            (0..9)
+                                .filter(|&pos| spots[a] & (1 << pos) != 0)
+                                .map(move |pos| (line, pos))
+                        })
+                        .find(|&(line, pos)| spots[line] & (1 << pos) != 0);
+                    if let Some((line, pos)) = victim {
+                        return Some(SolveStep::Eliminate {
+                            cell: cell(line, pos),
+                            digits: vec![digit],
+                            technique: Technique::XWing {
+                                digit,
+                                lines: [unit(a), unit(b)],
+                            },
+                        });
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Carry out a step on the grid and its candidates
+fn apply_step(grid: &mut [[usize; 9]; 9], cands: &mut Candidates, step: &SolveStep) {
+    match *step {
+        SolveStep::Place {
+            row, col, digit, ..
+        } => {
+            grid[row][col] = digit;
+            cands.place(row, col, digit);
+        }
+        SolveStep::Eliminate {
+            cell: (row, col),
+            ref digits,
+            ..
+        } => {
+            for &d in digits {
+                cands.remove(row, col, d);
+            }
+        }
+        SolveStep::GuessRequired { .. } => {}
+    }
+}
+
+/// The next deduction the technique library can justify, if any: a
+/// placement by a single, else an X-Wing elimination
+pub fn next_step(grid: &[[usize; 9]; 9], cands: &Candidates) -> Option<SolveStep> {
+    if let Some((row, col, digit)) = find_naked_single(grid, cands) {
+        return Some(SolveStep::Place {
+            row,
+            col,
+            digit,
+            technique: Technique::NakedSingle,
+        });
+    }
+    if let Some((row, col, digit, unit)) = find_hidden_single(grid, cands) {
+        return Some(SolveStep::Place {
+            row,
+            col,
+            digit,
+            technique: Technique::HiddenSingle(unit),
+        });
+    }
+    find_x_wing(grid, cands)
+}
+
+/// Every placement a single round of naked and hidden singles justifies from
+/// `grid` as it stands, as `(row, col, digit)` in row-major order: the "free
+/// moves". Nothing is applied, so placements that only follow from these are
+/// not included. A cell found by both techniques is listed once.
+pub fn naked_forced(grid: &[[usize; 9]; 9]) -> Vec<(usize, usize, usize)> {
+    let cands = Candidates::from_grid(grid);
+    let mut forced = [[0; 9]; 9];
+    for row in 0..9 {
+        for col in 0..9 {
+            if grid[row][col] == 0 && cands.count(row, col) == 1 {
+                forced[row][col] = cands.mask(row, col).trailing_zeros() as usize;
+            }
+        }
+    }
+    for unit in UNITS.iter() {
+        for d in 1..=9 {
+            let mut spots = unit
+                .iter()
+                .filter(|&&(r, c)| grid[r][c] == 0 && cands.contains(r, c, d));
+            if let (Some(&(r, c)), None) = (spots.next(), spots.next())
+                && forced[r][c] == 0
+            {
+                forced[r][c] = d;
+            }
+        }
+    }
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(row, col)| forced[row][col] != 0)
+        .map(|(row, col)| (row, col, forced[row][col]))
+        .collect()
+}
+
+/// A placement the technique library can justify, with the technique as its
+/// reason
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Hint {
+    pub row: usize,
+    pub col: usize,
+    pub digit: usize,
+    pub technique: Technique,
+    /// Elimination the placement only follows after, if the candidates
+    /// needed one first
+    pub after: Option<Technique>,
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "R{}C{} must be {}: ",
+            self.row + 1,
+            self.col + 1,
+            self.digit
+        )?;
+        match self.technique {
+            Technique::NakedSingle => write!(f, "it is the only candidate left in this cell"),
+            Technique::HiddenSingle(unit) => {
+                write!(
+                    f,
+                    "it is the only cell in {} that can hold {}",
+                    unit, self.digit
+                )
+            }
+            technique => write!(f, "it follows from an {}", technique),
+        }?;
+        match self.after {
+            Some(elimination) => write!(f, ", once the {} is applied", elimination),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The next placement the technique library finds from `grid` and `cands`,
+/// for a hint that explains itself instead of peeking at the solution.
+/// Eliminations are applied to a copy of `cands` until a single appears, and
+/// the first one is named in the hint. `None` when the grid is full or the
+/// techniques run out before a placement
+pub fn best_hint(grid: &[[usize; 9]; 9], cands: &Candidates) -> Option<Hint> {
+    let mut cands = *cands;
+    let mut after = None;
+    loop {
+        match next_step(grid, &cands)? {
+            SolveStep::Place {
+                row,
+                col,
+                digit,
+                technique,
+            } => {
+                return Some(Hint {
+                    row,
+                    col,
+                    digit,
+                    technique,
+                    after,
+                });
+            }
+            SolveStep::Eliminate {
+                cell: (row, col),
+                digits,
+                technique,
+            } => {
+                let mut removed = false;
+                for d in digits {
+                    removed |= cands.remove(row, col, d);
+                }
+                if !removed {
+                    return None;
+                }
+                after.get_or_insert(technique);
+            }
+            SolveStep::GuessRequired { .. } => return None,
+        }
+    }
+}
+
+/// Fill naked singles until none are left, returning the placements made in order
+pub fn propagate(grid: &mut [[usize; 9]; 9], cands: &mut Candidates) -> Vec<(usize, usize, usize)> {
+    let mut placed = Vec::new();
+    while let Some((row, col, d)) = find_naked_single(grid, cands) {
+        grid[row][col] = d;
+        cands.place(row, col, d);
+        placed.push((row, col, d));
+    }
+    placed
+}
+
+/// Cells filled by naked singles alone before getting stuck; a puzzle where
+/// this equals its number of empty cells is easy
+pub fn trivial_fill_count(puzzle: &[[usize; 9]; 9]) -> usize {
+    let mut grid = *puzzle;
+    let mut cands = Candidates::from_grid(&grid);
+    propagate(&mut grid, &mut cands).len()
+}
+
+/// First empty cell left without candidates after one naked-single pass.
+///
+/// A cheap dead-end check for interactive editing: it never searches, so it
+/// misses deeper contradictions but reports the common ones instantly.
+pub fn find_dead_cell(grid: &[[usize; 9]; 9]) -> Option<(usize, usize)> {
+    let mut grid = *grid;
+    let mut cands = Candidates::from_grid(&grid);
+    let singles: Vec<_> = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(row, col)| grid[row][col] == 0 && cands.count(row, col) == 1)
+        .map(|(row, col)| (row, col, cands.mask(row, col).trailing_zeros() as usize))
+        .collect();
+    // Two singles forcing the same digit into one unit leave the second empty
+    for (row, col, d) in singles {
+        if cands.contains(row, col, d) {
+            grid[row][col] = d;
+            cands.place(row, col, d);
+        }
+    }
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .find(|&(row, col)| grid[row][col] == 0 && cands.count(row, col) == 0)
+}
+
+/// Solve by logic alone, recording each deduction in order.
+///
+/// If the techniques run out before the grid is full, the trace ends with
+/// `SolveStep::GuessRequired`.
+pub fn solve_with_trace(puzzle: &[[usize; 9]; 9]) -> Vec<SolveStep> {
+    let mut grid = *puzzle;
+    let mut cands = Candidates::from_grid(&grid);
+    let mut steps = Vec::new();
+    while let Some(step) = next_step(&grid, &cands) {
+        apply_step(&mut grid, &mut cands, &step);
+        steps.push(step);
+    }
+    let empty = grid.iter().flatten().filter(|&&d| d == 0).count();
+    if empty > 0 {
+        steps.push(SolveStep::GuessRequired { empty });
+    }
+    steps
+}
+
+/// Solve with the technique library alone: the solution if the deductions
+/// fill every cell, `None` if the puzzle needs guessing or has no solution
+pub fn solve_logic_only(puzzle: &[[usize; 9]; 9]) -> Option<[[usize; 9]; 9]> {
+    if has_conflicts(puzzle) {
+        return None;
+    }
+    let mut grid = *puzzle;
+    let mut cands = Candidates::from_grid(&grid);
+    while let Some(step) = next_step(&grid, &cands) {
+        apply_step(&mut grid, &mut cands, &step);
+    }
+    grid.iter().flatten().all(|&d| d != 0).then_some(grid)
+}
+
+/// Solve preferring logic: techniques run to a fixpoint and a guess is only
+/// made when they are stuck, on the empty cell with the fewest candidates.
+///
+/// Returns the solution and the number of guesses tried (every branch counts,
+/// including ones that were backed out), which serves as a difficulty proxy.
+pub fn solve_humanlike(puzzle: &[[usize; 9]; 9]) -> (Option<[[usize; 9]; 9]>, usize) {
+    if has_conflicts(puzzle) {
+        return (None, 0);
+    }
+    let mut guesses = 0;
+    let solution = solve_humanlike_from(*puzzle, &mut guesses);
+    (solution, guesses)
+}
+
+fn solve_humanlike_from(mut grid: [[usize; 9]; 9], guesses: &mut usize) -> Option<[[usize; 9]; 9]> {
+    let mut cands = Candidates::from_grid(&grid);
+    while let Some(step) = next_step(&grid, &cands) {
+        apply_step(&mut grid, &mut cands, &step);
+    }
+
+    // Most constrained empty cell; none left means every placement was a
+    // candidate, so the grid is a valid solution
+    let Some((row, col)) = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(r, c)| grid[r][c] == 0)
+        .min_by_key(|&(r, c)| cands.count(r, c))
+    else {
+        return Some(grid);
+    };
+
+    for d in cands.digits(row, col) {
+        *guesses += 1;
+        let mut next = grid;
+        next[row][col] = d;
+        if let Some(solution) = solve_humanlike_from(next, guesses) {
+            return Some(solution);
+        }
+    }
+    None
+}
+
+/// Rough human difficulty, from the techniques and guesses a puzzle needs
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Difficulty {
+    /// Singles and X-Wings solve it without guessing
+    Easy,
+    /// At most two guesses when those run out
+    Medium,
+    /// At most ten guesses
+    Hard,
+    /// Anything needing more guessing
+    Expert,
+}
+
+impl Difficulty {
+    /// Every rating, easiest first
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    /// Parse a rating as `Display` writes it (`easy`, `medium`, `hard`, `expert`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|d| d.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Rate a puzzle by the guesses `solve_humanlike` needs; `None` if it has no solution
+pub fn estimate_difficulty(puzzle: &[[usize; 9]; 9]) -> Option<Difficulty> {
+    let (solution, guesses) = solve_humanlike(puzzle);
+    solution?;
+    Some(match guesses {
+        0 => Difficulty::Easy,
+        1..=2 => Difficulty::Medium,
+        3..=10 => Difficulty::Hard,
+        _ => Difficulty::Expert,
+    })
+}
+
+/// Bounded memo for `estimate_difficulty`, keyed by the puzzle as it stands.
+/// The oldest entry is evicted once `capacity` is reached.
+///
+/// The guess count behind a rating depends on the order cells and digits are
+/// tried in, so symmetric copies can rate differently (the hard sample turned
+/// 90° rates medium). Each copy therefore gets its own entry, and a cached
+/// rating always equals `estimate_difficulty` of the same grid.
+pub struct RatingCache {
+    capacity: usize,
+    ratings: HashMap<GridKey, Option<Difficulty>>,
+    order: VecDeque<GridKey>,
+}
+
+impl RatingCache {
+    pub fn new(capacity: usize) -> Self {
+        RatingCache {
+            capacity,
+            ratings: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Cached rating of `puzzle`, computing and storing it on a miss
+    pub fn rating(&mut self, puzzle: &[[usize; 9]; 9]) -> Option<Difficulty> {
+        let key = GridKey::from(puzzle);
+        if let Some(&rating) = self.ratings.get(&key) {
+            return rating;
+        }
+        let rating = estimate_difficulty(puzzle);
+        if self.capacity > 0 {
+            if self.order.len() >= self.capacity
+                && let Some(oldest) = self.order.pop_front()
+            {
+                self.ratings.remove(&oldest);
+            }
+            self.order.push_back(key.clone());
+            self.ratings.insert(key, rating);
+        }
+        rating
+    }
+
+    /// Number of ratings currently stored
+    pub fn len(&self) -> usize {
+        self.ratings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ratings.is_empty()
+    }
+}
@@ -1,28 +1,375 @@
 pub mod sat;
 pub mod backtracking;
+pub mod auto;
+pub mod check;
+pub mod exact_cover;
+pub mod logic;
+
+use crate::grid::variant_units;
+use std::time::Duration;
+
+/// Which unit constraints a puzzle uses besides rows and columns
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Variant {
+    /// Standard Sudoku: rows, columns and 3x3 boxes
+    #[default]
+    Classic,
+    /// Rows and columns only, boxes are ignored
+    LatinSquare,
+}
+
+impl Variant {
+    pub fn has_boxes(self) -> bool {
+        matches!(self, Variant::Classic)
+    }
+}
+
+/// Ordering constraint for comparison variants: the digit in cell `a` must be
+/// greater than the one in cell `b`. Cells are 0-indexed `(row, col)`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct GreaterThan {
+    pub a: (usize, usize),
+    pub b: (usize, usize),
+}
+
+impl GreaterThan {
+    /// Whether `digit` may go in `(row, col)` given the other cell's current
+    /// value; an empty other cell or an unrelated cell allows anything, and so
+    /// does an other cell off the grid (solvers reject those relations first)
+    pub fn allows(&self, grid: &[[usize; 9]; 9], row: usize, col: usize, digit: usize) -> bool {
+        let value = |(r, c): (usize, usize)| grid.get(r).and_then(|cells| cells.get(c)).copied();
+        if (row, col) == self.a {
+            let other = value(self.b).unwrap_or(0);
+            other == 0 || digit > other
+        } else if (row, col) == self.b {
+            let other = value(self.a).unwrap_or(0);
+            other == 0 || other > digit
+        } else {
+            true
+        }
+    }
+}
+
+/// Odd/even variant marking: the cell's digit must have this parity
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Parity {
+    Odd,
+    Even,
+}
+
+impl Parity {
+    pub fn allows(self, digit: usize) -> bool {
+        (digit % 2 == 1) == (self == Parity::Odd)
+    }
+
+    /// The digits 1-9 of this parity
+    pub fn digits(self) -> Vec<usize> {
+        (1..=9).filter(|&d| self.allows(d)).collect()
+    }
+}
+
+/// Why a solver returned no grid
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SolveError {
+    /// The puzzle has no solution
+    Unsatisfiable,
+    /// The search visited more nodes than the solver's limit allows
+    NodeLimitExceeded { limit: usize },
+    /// The SAT encoding would not fit in the clause budget
+    ClauseBudgetExceeded(sat::ClauseBudgetExceeded),
+    /// The SAT backend was stopped before reaching an answer (e.g. by a timeout)
+    Interrupted,
+    /// The SAT backend reported an error
+    SolverBackendError(String),
+    /// A cell of the puzzle holds something other than 0-9 (0-indexed cell)
+    InvalidDigit {
+        row: usize,
+        col: usize,
+        digit: usize,
+    },
+    /// A `(row, col, digit)` placement passed alongside the puzzle lies off the
+    /// grid or names a digit outside 1-9 (0-indexed cell)
+    InvalidPlacement {
+        row: usize,
+        col: usize,
+        digit: usize,
+    },
+    /// A flat puzzle buffer is not one value per cell
+    WrongLength { expected: usize, found: usize },
+}
+
+impl std::fmt::Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Unsatisfiable => write!(f, "puzzle has no solution"),
+            SolveError::NodeLimitExceeded { limit } => {
+                write!(f, "search gave up after {} nodes", limit)
+            }
+            SolveError::ClauseBudgetExceeded(e) => e.fmt(f),
+            SolveError::Interrupted => write!(f, "solver was interrupted before finishing"),
+            SolveError::SolverBackendError(msg) => write!(f, "SAT backend error: {}", msg),
+            SolveError::InvalidDigit { row, col, digit } => {
+                write!(
+                    f,
+                    "R{}C{} holds {}, not a digit 1-9",
+                    row + 1,
+                    col + 1,
+                    digit
+                )
+            }
+            SolveError::InvalidPlacement { row, col, digit } => {
+                write!(
+                    f,
+                    "R{}C{}={} is not a placement of a digit 1-9 on the grid",
+                    row + 1,
+                    col + 1,
+                    digit
+                )
+            }
+            SolveError::WrongLength { expected, found } => {
+                write!(f, "expected {} cells, got {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// `InvalidDigit` for the first cell (row-major) holding more than 9
+fn check_digits(puzzle: &[[usize; 9]; 9]) -> Result<(), SolveError> {
+    match (0..81).find(|&i| puzzle[i / 9][i % 9] > 9) {
+        Some(i) => Err(SolveError::InvalidDigit {
+            row: i / 9,
+            col: i % 9,
+            digit: puzzle[i / 9][i % 9],
+        }),
+        None => Ok(()),
+    }
+}
+
+/// `InvalidPlacement` for the first restriction naming a cell off the grid
+/// or a digit outside 1-9; an off-grid cell reports its first digit (or 0)
+fn check_restrictions(restrictions: &[((usize, usize), Vec<usize>)]) -> Result<(), SolveError> {
+    for &((row, col), ref digits) in restrictions {
+        let off_grid = row >= 9 || col >= 9;
+        let bad_digit = digits.iter().find(|d| !(1..=9).contains(*d));
+        if off_grid || bad_digit.is_some() {
+            let digit = bad_digit.or(digits.first()).copied().unwrap_or(0);
+            return Err(SolveError::InvalidPlacement { row, col, digit });
+        }
+    }
+    Ok(())
+}
+
+/// `InvalidPlacement` (with digit 0) for the first relation naming a cell off
+/// the grid
+fn check_inequalities(inequalities: &[GreaterThan]) -> Result<(), SolveError> {
+    match inequalities
+        .iter()
+        .flat_map(|i| [i.a, i.b])
+        .find(|&(row, col)| row >= 9 || col >= 9)
+    {
+        Some((row, col)) => Err(SolveError::InvalidPlacement { row, col, digit: 0 }),
+        None => Ok(()),
+    }
+}
+
+/// Fast path shared by the solvers, run before any search: a cell outside
+/// 0-9 is an `InvalidDigit` error, and a grid without empty cells is its own
+/// solution if no unit repeats a digit, and unsatisfiable otherwise.
+/// `None` means the grid still has empty cells and needs a real search.
+fn solve_complete(
+    puzzle: &[[usize; 9]; 9],
+    variant: Variant,
+) -> Option<Result<[[usize; 9]; 9], SolveError>> {
+    if let Err(e) = check_digits(puzzle) {
+        return Some(Err(e));
+    }
+    if puzzle.iter().flatten().any(|&d| d == 0) {
+        return None;
+    }
+    let valid = variant_units(variant).iter().all(|unit| {
+        let mut seen = 0u16;
+        unit.iter().all(|&(row, col)| {
+            let bit = 1 << puzzle[row][col];
+            let fresh = seen & bit == 0;
+            seen |= bit;
+            fresh
+        })
+    });
+    Some(if valid {
+        Ok(*puzzle)
+    } else {
+        Err(SolveError::Unsatisfiable)
+    })
+}
 
 pub trait SudokuSolver {
-    fn solve(&mut self, puzzle: &[[usize; 9]; 9]) -> Option<[[usize; 9]; 9]>;
+    fn solve(&mut self, puzzle: &[[usize; 9]; 9]) -> Result<[[usize; 9]; 9], SolveError>;
+
+    /// Whether `puzzle` has a solution, without handing one back. Solvers can
+    /// override this to skip building the grid; `Unsatisfiable` becomes
+    /// `Ok(false)` and other errors pass through.
+    fn is_satisfiable(&mut self, puzzle: &[[usize; 9]; 9]) -> Result<bool, SolveError> {
+        satisfiable(self.solve(puzzle))
+    }
+
+    /// `solve` over row-major flat buffers, for bindings and callers storing
+    /// grids flat. A buffer that isn't 81 cells long is `WrongLength`; other
+    /// errors are `solve`'s.
+    fn solve_flat(&mut self, puzzle: &[usize]) -> Result<Vec<usize>, SolveError> {
+        if puzzle.len() != 81 {
+            return Err(SolveError::WrongLength {
+                expected: 81,
+                found: puzzle.len(),
+            });
+        }
+        let mut grid = [[0; 9]; 9];
+        for (i, &d) in puzzle.iter().enumerate() {
+            grid[i / 9][i % 9] = d;
+        }
+        let solution = self.solve(&grid)?;
+        Ok(solution.into_iter().flatten().collect())
+    }
+}
+
+/// A solve result as `is_satisfiable` reports it
+fn satisfiable(result: Result<[[usize; 9]; 9], SolveError>) -> Result<bool, SolveError> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(SolveError::Unsatisfiable) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Text of a caught panic's payload
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "unknown panic"
+    }
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum SolverKind {
     Sat,
     Backtracking,
-    // ExactCover,
+    Auto,
+    ExactCover,
+}
+
+impl SolverKind {
+    /// Every implemented solver
+    pub const ALL: [SolverKind; 4] = [
+        SolverKind::Sat,
+        SolverKind::Backtracking,
+        SolverKind::Auto,
+        SolverKind::ExactCover,
+    ];
+
+    /// Parse a solver name as used on the command line (`sat`, `backtracking`, `auto`,
+    /// `exact-cover`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sat" => Some(SolverKind::Sat),
+            "backtracking" | "bt" => Some(SolverKind::Backtracking),
+            "auto" => Some(SolverKind::Auto),
+            "exact-cover" | "dlx" => Some(SolverKind::ExactCover),
+            _ => None,
+        }
+    }
 }
 
 pub enum Solver {
     Sat(sat::SatSudokuSolver),
     Backtracking(backtracking::BacktrackingSudokuSolver),
-    // ExactCover(exact_cover::ExactCoverSudokuSolver),
+    Auto(auto::AutoSudokuSolver),
+    ExactCover(exact_cover::ExactCoverSudokuSolver),
 }
 
 impl SudokuSolver for Solver {
-    fn solve(&mut self, puzzle: &[[usize; 9]; 9]) -> Option<[[usize; 9]; 9]> {
+    fn solve(&mut self, puzzle: &[[usize; 9]; 9]) -> Result<[[usize; 9]; 9], SolveError> {
         match self {
             Solver::Sat(s) => s.solve(puzzle),
             Solver::Backtracking(s) => s.solve(puzzle),
-            // Solver::ExactCover(s) => s.solve(puzzle),
+            Solver::Auto(s) => s.solve(puzzle),
+            Solver::ExactCover(s) => s.solve(puzzle),
+        }
+    }
+
+    fn is_satisfiable(&mut self, puzzle: &[[usize; 9]; 9]) -> Result<bool, SolveError> {
+        match self {
+            Solver::Sat(s) => s.is_satisfiable(puzzle),
+            Solver::Backtracking(s) => s.is_satisfiable(puzzle),
+            Solver::Auto(s) => s.is_satisfiable(puzzle),
+            Solver::ExactCover(s) => s.is_satisfiable(puzzle),
+        }
+    }
+}
+
+impl Solver {
+    pub fn with_variant(self, variant: Variant) -> Self {
+        match self {
+            Solver::Sat(s) => Solver::Sat(s.with_variant(variant)),
+            Solver::Backtracking(s) => Solver::Backtracking(s.with_variant(variant)),
+            Solver::Auto(s) => Solver::Auto(s.with_variant(variant)),
+            Solver::ExactCover(s) => Solver::ExactCover(s.with_variant(variant)),
+        }
+    }
+
+    /// Shuffle the SAT clause order with `seed`; other solvers are unaffected
+    pub fn with_shuffled_clauses(self, seed: u64) -> Self {
+        match self {
+            Solver::Sat(s) => Solver::Sat(s.with_shuffled_clauses(seed)),
+            other => other,
+        }
+    }
+
+    /// Record the SAT encode/solve split; other solvers are unaffected
+    pub fn with_timings(self) -> Self {
+        match self {
+            Solver::Sat(s) => Solver::Sat(s.with_timings()),
+            other => other,
+        }
+    }
+
+    /// The SAT encode/solve split of the last solve, if recorded
+    pub fn sat_times(&self) -> Option<sat::SolveTimes> {
+        match self {
+            Solver::Sat(s) => s.last_times(),
+            _ => None,
+        }
+    }
+
+    /// The SAT model problem behind the last solve's grid, if there was one
+    pub fn sat_model_error(&self) -> Option<&sat::ModelError> {
+        match self {
+            Solver::Sat(s) => s.last_model_error(),
+            _ => None,
+        }
+    }
+
+    /// Cap the search size of the backtracking and exact cover solvers; SAT
+    /// and auto (whose backtracking stage has its own budget) are unaffected
+    pub fn with_max_nodes(self, limit: usize) -> Self {
+        match self {
+            Solver::Backtracking(s) => Solver::Backtracking(s.with_max_nodes(limit)),
+            Solver::ExactCover(s) => Solver::ExactCover(s.with_max_nodes(limit)),
+            other => other,
+        }
+    }
+
+    /// Give up with `Interrupted` once `timeout` has passed in the SAT solver
+    /// and auto's SAT stage; backtracking and exact cover are bounded by
+    /// `with_max_nodes` instead
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        match self {
+            Solver::Sat(s) => Solver::Sat(s.with_timeout(timeout)),
+            Solver::Auto(s) => Solver::Auto(s.with_timeout(timeout)),
+            other => other,
         }
     }
 }
@@ -31,6 +378,7 @@ pub fn make_solver(kind: SolverKind) -> Solver {
     match kind {
         SolverKind::Sat => Solver::Sat(sat::SatSudokuSolver::default()),
         SolverKind::Backtracking => Solver::Backtracking(backtracking::BacktrackingSudokuSolver::default()),
-        // SolverKind::ExactCover => Solver::ExactCover(exact_cover::ExactCoverSudokuSolver::default()),
+        SolverKind::Auto => Solver::Auto(auto::AutoSudokuSolver::default()),
+        SolverKind::ExactCover => Solver::ExactCover(exact_cover::ExactCoverSudokuSolver::default()),
     }
 }
@@ -1,31 +1,729 @@
 use rustsat::clause;
-use rustsat::instances::SatInstance;
-use rustsat::solvers::Solve;
-use rustsat::solvers::SolverResult::Sat;
-use rustsat::types::{Assignment, Lit, TernaryVal};
+use rustsat::encodings::card::{BoundUpper, Totalizer};
+use rustsat::instances::{Objective, OptInstance, SatInstance};
+use rustsat::solvers::{ControlSignal, PhaseLit, Solve, SolveIncremental, SolverResult, Terminate};
+use rustsat::types::{Assignment, Clause, Lit, TernaryVal, Var};
 
 use rustsat_cadical::CaDiCaL;
 
-use super::SudokuSolver;
+use super::{
+    GreaterThan, Parity, SolveError, SudokuSolver, Variant, check_digits, check_inequalities,
+    check_restrictions, satisfiable, solve_complete,
+};
+use crate::generator::Rng;
+use crate::grid::{UNITS, box_cells, can_place};
+use std::time::{Duration, Instant};
 
-#[derive(Default)]
-pub struct SatSudokuSolver;
+/// Default cap on the number of clauses built; the classic encoding needs under 10k
+pub const DEFAULT_CLAUSE_BUDGET: usize = 1_000_000;
 
-impl SudokuSolver for SatSudokuSolver {
-    fn solve(&mut self, puzzle: &[[usize; 9]; 9]) -> Option<[[usize; 9]; 9]> {
+pub struct SatSudokuSolver {
+    variant: Variant,
+    clause_budget: usize,
+    /// Seed for shuffling the clause order before it reaches the backend
+    shuffle_seed: Option<u64>,
+    /// Wall-clock limit for one backend call
+    timeout: Option<Duration>,
+    /// Redundant clause families added to the minimal encoding
+    redundancy: Redundancy,
+    /// Order of the clue assumptions in `solve_assuming_clues`
+    clue_order: ClueOrder,
+    /// Backend search settings pinned by `with_search_controls`
+    search: SearchControls,
+    /// Whether `solve_with_assignment` measures `last_times`
+    record_times: bool,
+    last_times: Option<SolveTimes>,
+    /// Cells of the last model that didn't hold exactly one digit
+    last_model_error: Option<ModelError>,
+}
+
+impl Default for SatSudokuSolver {
+    fn default() -> Self {
+        Self {
+            variant: Variant::default(),
+            clause_budget: DEFAULT_CLAUSE_BUDGET,
+            shuffle_seed: None,
+            timeout: None,
+            redundancy: Redundancy::default(),
+            clue_order: ClueOrder::default(),
+            search: SearchControls::default(),
+            record_times: false,
+            last_times: None,
+            last_model_error: None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ClauseBudgetExceeded {
+    pub estimated: usize,
+    pub budget: usize,
+}
+
+impl std::fmt::Display for ClauseBudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "encoding needs about {} clauses, over the budget of {}",
+            self.estimated, self.budget
+        )
+    }
+}
+
+impl std::error::Error for ClauseBudgetExceeded {}
+
+/// Order in which clues are handed to the backend as assumptions
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ClueOrder {
+    /// Row-major, the same on every run
+    #[default]
+    RowMajor,
+    /// Clues in the cells with the fewest candidates first (counting only the
+    /// other clues), which can surface a contradiction sooner. Ties keep
+    /// row-major order.
+    MostConstrained,
+}
+
+/// CaDiCaL search settings to pin for reproducible runs; `None` keeps the
+/// backend's default. The binding exposes CaDiCaL's named options and
+/// per-literal phases but not its variable decision order, so these are the
+/// knobs that can be fixed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchControls {
+    /// CaDiCaL's random seed (option `seed`)
+    pub seed: Option<u32>,
+    /// Value every variable is decided to first (option `phase`), used
+    /// instead of the saved phase on every decision (option `forcephase`)
+    pub phase: Option<bool>,
+    /// `(row, col, digit)` placements (0-indexed cell, digit 1-9) whose literals are
+    /// decided true first, overriding `phase` for them
+    pub preferred: Vec<(usize, usize, usize)>,
+}
+
+/// Where the time of one SAT solve went
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SolveTimes {
+    /// Building the CNF and loading it into the backend
+    pub encode_time: Duration,
+    /// The backend's `solve()` call
+    pub solve_time: Duration,
+}
+
+impl SatSudokuSolver {
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Refuse to build encodings estimated to exceed `budget` clauses
+    pub fn with_clause_budget(mut self, budget: usize) -> Self {
+        self.clause_budget = budget;
+        self
+    }
+
+    /// Hand the clauses to the backend in a random order drawn from `seed`,
+    /// for measuring how sensitive the backend is to input order. The
+    /// default keeps the order in which the encoding builds them.
+    pub fn with_shuffled_clauses(mut self, seed: u64) -> Self {
+        self.shuffle_seed = Some(seed);
+        self
+    }
+
+    /// Stop the backend after `timeout`, failing with `SolveError::Interrupted`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Add the redundant clause families switched on in `redundancy`
+    pub fn with_redundancy(mut self, redundancy: Redundancy) -> Self {
+        self.redundancy = redundancy;
+        self
+    }
+
+    /// Pass the clues to the backend in this order in `solve_assuming_clues`
+    pub fn with_clue_order(mut self, order: ClueOrder) -> Self {
+        self.clue_order = order;
+        self
+    }
+
+    /// Pin the backend's seed and decision phases; the defaults leave CaDiCaL
+    /// unconfigured
+    pub fn with_search_controls(mut self, controls: SearchControls) -> Self {
+        self.search = controls;
+        self
+    }
+
+    /// Measure encoding and solving separately on every solve; off by default
+    pub fn with_timings(mut self) -> Self {
+        self.record_times = true;
+        self
+    }
+
+    /// Timings of the last solve that reached the backend, if enabled
+    pub fn last_times(&self) -> Option<SolveTimes> {
+        self.last_times
+    }
+
+    /// Why the last model wasn't a clean grid, if it wasn't. Only an encoding
+    /// bug can cause this; the returned grid then holds each such cell's
+    /// lowest true digit, or 0.
+    pub fn last_model_error(&self) -> Option<&ModelError> {
+        self.last_model_error.as_ref()
+    }
+
+    /// `read_model`, keeping the error for `last_model_error`
+    fn extract_grid(&mut self, sudoku: &SudokuSat, sol: &Assignment) -> [[usize; 9]; 9] {
+        let (grid, error) = read_model(sudoku, sol);
+        self.last_model_error = error;
+        grid
+    }
+
+    /// Clauses the constraint encoding will produce for this configuration
+    /// (its variant and redundant families), not counting the (at most 81)
+    /// unit clauses for the puzzle's clues or the clauses of variant
+    /// constraints such as `solve_with_inequalities`, which the budget check
+    /// adds on top
+    pub fn estimated_clause_count(&self) -> usize {
+        // Pairwise at-most-one: one binary clause per pair of cells, per unit and digit
+        let pairs_per_unit = 9 * 8 / 2;
+        let units = if self.variant.has_boxes() { 27 } else { 18 };
+        81 + units * 9 * pairs_per_unit + self.redundancy.clause_count(self.variant)
+    }
+
+    /// Check the estimate against the budget before building anything
+    pub fn check_clause_budget(&self) -> Result<usize, ClauseBudgetExceeded> {
+        self.check_clause_budget_with(0)
+    }
+
+    /// Budget check for an encoding with `extra` variant clauses on top
+    fn check_clause_budget_with(&self, extra: usize) -> Result<usize, ClauseBudgetExceeded> {
+        let estimated = self.estimated_clause_count() + extra;
+        if estimated > self.clause_budget {
+            Err(ClauseBudgetExceeded {
+                estimated,
+                budget: self.clause_budget,
+            })
+        } else {
+            Ok(estimated)
+        }
+    }
+}
+
+/// Size of the CNF built for one puzzle, with clauses split by constraint category
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct EncodingInfo {
+    pub vars: u32,
+    pub cell_at_least_one: usize,
+    pub row_amo: usize,
+    pub col_amo: usize,
+    pub box_amo: usize,
+    /// Clauses from the `Redundancy` families, all together
+    pub redundant: usize,
+    pub clues: usize,
+}
+
+impl EncodingInfo {
+    pub fn total_clauses(&self) -> usize {
+        self.cell_at_least_one
+            + self.row_amo
+            + self.col_amo
+            + self.box_amo
+            + self.redundant
+            + self.clues
+    }
+}
+
+/// Clause families implied by the minimal encoding. They never change the
+/// solutions, only how quickly the backend finds them, so each can be switched
+/// on separately to study which ones help.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Redundancy {
+    /// At most one digit per cell
+    pub cell_amo: bool,
+    /// Every digit at least once per row
+    pub row_alo: bool,
+    /// Every digit at least once per column
+    pub col_alo: bool,
+    /// Every digit at least once per box (ignored without boxes)
+    pub box_alo: bool,
+}
+
+impl Redundancy {
+    pub const ALL: Redundancy = Redundancy {
+        cell_amo: true,
+        row_alo: true,
+        col_alo: true,
+        box_alo: true,
+    };
+
+    /// All 16 on/off combinations, starting with none
+    pub fn combinations() -> impl Iterator<Item = Redundancy> {
+        (0..16u8).map(|bits| Redundancy {
+            cell_amo: bits & 1 != 0,
+            row_alo: bits & 2 != 0,
+            col_alo: bits & 4 != 0,
+            box_alo: bits & 8 != 0,
+        })
+    }
+
+    fn clause_count(&self, variant: Variant) -> usize {
+        let alo_units = [
+            self.row_alo,
+            self.col_alo,
+            self.box_alo && variant.has_boxes(),
+        ];
+        let cell_amo = if self.cell_amo { 81 * 9 * 8 / 2 } else { 0 };
+        cell_amo + alo_units.iter().filter(|&&on| on).count() * 9 * 9
+    }
+}
+
+impl std::fmt::Display for Redundancy {
+    /// `+`-separated family names, or `none`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = [
+            (self.cell_amo, "cell_amo"),
+            (self.row_alo, "row_alo"),
+            (self.col_alo, "col_alo"),
+            (self.box_alo, "box_alo"),
+        ]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join("+"))
+        }
+    }
+}
+
+impl SatSudokuSolver {
+    /// Build the encoding for `puzzle` without solving it
+    pub fn encoding_info(&self, puzzle: &[[usize; 9]; 9]) -> EncodingInfo {
         let mut model = SudokuSat::new();
-        add_minimal_sudoku_constraints(&mut model);
-        add_puzzle_clues(&mut model, puzzle);
+        let mut info = add_minimal_sudoku_constraints(&mut model, self.variant);
+        info.redundant = add_redundant_clauses(&mut model, self.redundancy, self.variant);
+        info.clues = add_puzzle_clues(&mut model, puzzle);
+        info.vars = model.instance.n_vars();
+        info
+    }
+}
+
+impl SatSudokuSolver {
+    /// Encode `puzzle` and hand the CNF to a fresh backend
+    fn load(
+        &self,
+        puzzle: &[[usize; 9]; 9],
+    ) -> Result<(SudokuSat, CaDiCaL<'static, 'static>), SolveError> {
+        self.load_with_extra(puzzle, 0)
+    }
+
+    /// `load`, budgeting for `extra` clauses the caller adds afterwards
+    fn load_with_extra(
+        &self,
+        puzzle: &[[usize; 9]; 9],
+        extra: usize,
+    ) -> Result<(SudokuSat, CaDiCaL<'static, 'static>), SolveError> {
+        check_digits(puzzle)?;
+        // Fail fast rather than building an encoding that would not fit in memory
+        self.check_clause_budget_with(extra)
+            .map_err(SolveError::ClauseBudgetExceeded)?;
+
+        let mut model = build_model(puzzle, self.variant);
+        add_redundant_clauses(&mut model, self.redundancy, self.variant);
+
+        let mut cnf = model.instance.clone().into_cnf().0;
+        if let Some(seed) = self.shuffle_seed {
+            let mut clauses: Vec<Clause> = cnf.into_iter().collect();
+            Rng::new(seed).shuffle(&mut clauses);
+            cnf = clauses.into_iter().collect();
+        }
 
         let mut solver = CaDiCaL::default();
-        solver.add_cnf(model.instance.clone().into_cnf().0).unwrap();
+        // Options must be set before any clause is added
+        if let Some(seed) = self.search.seed {
+            backend(solver.set_option("seed", seed as i32))?;
+        }
+        if let Some(phase) = self.search.phase {
+            backend(solver.set_option("phase", phase as i32))?;
+            backend(solver.set_option("forcephase", 1))?;
+        }
+        backend(solver.add_cnf(cnf))?;
+        for &(row, col, digit) in &self.search.preferred {
+            backend(solver.phase_lit(placement_literal(&model, row, col, digit)?))?;
+        }
+        if let Some(timeout) = self.timeout {
+            let deadline = Instant::now() + timeout;
+            solver.attach_terminator(move || {
+                if Instant::now() >= deadline {
+                    ControlSignal::Terminate
+                } else {
+                    ControlSignal::Continue
+                }
+            });
+        }
+        Ok((model, solver))
+    }
+
+    /// Solve with only the rules in the CNF and the clues passed as
+    /// assumptions, in the order set by `with_clue_order`
+    pub fn solve_assuming_clues(
+        &mut self,
+        puzzle: &[[usize; 9]; 9],
+    ) -> Result<[[usize; 9]; 9], SolveError> {
+        check_digits(puzzle)?;
+        let (model, mut solver) = self.load(&[[0; 9]; 9])?;
+        let mut clues: Vec<(usize, usize, usize)> = givens(puzzle).collect();
+        if self.clue_order == ClueOrder::MostConstrained {
+            clues.sort_by_key(|&(row, col, _)| {
+                let mut others = *puzzle;
+                others[row][col] = 0;
+                (1..=9).filter(|&d| can_place(&others, row, col, d)).count()
+            });
+        }
+        let assumptions: Vec<Lit> = clues
+            .iter()
+            .map(|&(row, col, digit)| model.literals[row][col][digit - 1])
+            .collect();
+        let result = backend(solver.solve_assumps(&assumptions))?;
+        let sol = model_or_error(&mut solver, result)?;
+        Ok(self.extract_grid(&model, &sol))
+    }
+
+    /// Solve with some cells limited to the listed digits, e.g.
+    /// `((0, 4), vec![2, 4, 7])`; each restriction becomes unit clauses
+    /// forbidding the other digits in that cell. A cell off the grid or a
+    /// digit outside 1-9 is an `InvalidPlacement` error
+    pub fn solve_with_restrictions(
+        &mut self,
+        puzzle: &[[usize; 9]; 9],
+        restrictions: &[((usize, usize), Vec<usize>)],
+    ) -> Result<[[usize; 9]; 9], SolveError> {
+        check_restrictions(restrictions)?;
+        let excluded: usize = restrictions
+            .iter()
+            .map(|(_, digits)| (1..=9).filter(|d| !digits.contains(d)).count())
+            .sum();
+        let (model, mut solver) = self.load_with_extra(puzzle, excluded)?;
+        for ((row, col), digits) in restrictions {
+            for digit in (1..=9).filter(|d| !digits.contains(d)) {
+                backend(solver.add_clause(clause![!model.literals[*row][*col][digit - 1]]))?;
+            }
+        }
+        let result = backend(solver.solve())?;
+        let sol = model_or_error(&mut solver, result)?;
+        Ok(self.extract_grid(&model, &sol))
+    }
 
-        match solver.solve().unwrap() {
-            Sat => {
-                let sol = solver.full_solution().unwrap();
-                Some(extract_grid(&model, &sol))
+    /// Solve with the marked cells limited to digits of their parity, by
+    /// unit clauses forbidding the other digits
+    pub fn solve_with_parities(
+        &mut self,
+        puzzle: &[[usize; 9]; 9],
+        parities: &[((usize, usize), Parity)],
+    ) -> Result<[[usize; 9]; 9], SolveError> {
+        let restrictions: Vec<_> = parities
+            .iter()
+            .map(|&(cell, parity)| (cell, parity.digits()))
+            .collect();
+        self.solve_with_restrictions(puzzle, &restrictions)
+    }
+
+    /// Solve with every `GreaterThan` relation enforced: for each pair of
+    /// digits with `a <= b`, a binary clause forbids that combination. A
+    /// relation naming a cell off the grid is an `InvalidPlacement` error
+    pub fn solve_with_inequalities(
+        &mut self,
+        puzzle: &[[usize; 9]; 9],
+        inequalities: &[GreaterThan],
+    ) -> Result<[[usize; 9]; 9], SolveError> {
+        check_inequalities(inequalities)?;
+        // One clause per digit pair with `da <= db`
+        let extra = inequalities.len() * 45;
+        let (model, mut solver) = self.load_with_extra(puzzle, extra)?;
+        for &GreaterThan { a, b } in inequalities {
+            for da in 1..=9 {
+                for db in da..=9 {
+                    backend(solver.add_clause(clause![
+                        !model.literals[a.0][a.1][da - 1],
+                        !model.literals[b.0][b.1][db - 1]
+                    ]))?;
+                }
             }
-            _ => None,
+        }
+        let result = backend(solver.solve())?;
+        let sol = model_or_error(&mut solver, result)?;
+        Ok(self.extract_grid(&model, &sol))
+    }
+
+    /// Solve, honouring the 0-indexed `(row, col, digit)` preferences if some
+    /// solution allows it.
+    ///
+    /// Preferences are soft: all of them are passed to the backend as
+    /// assumptions, and if no solution satisfies every one they are dropped
+    /// together and the puzzle is solved plain. There is no partial relaxation,
+    /// so the result fails only when the puzzle itself has no solution.
+    pub fn solve_preferring(
+        &mut self,
+        puzzle: &[[usize; 9]; 9],
+        preferences: &[(usize, usize, usize)],
+    ) -> Result<[[usize; 9]; 9], SolveError> {
+        let (model, mut solver) = self.load(puzzle)?;
+        let assumptions = preferences
+            .iter()
+            .map(|&(row, col, digit)| placement_literal(&model, row, col, digit))
+            .collect::<Result<Vec<Lit>, _>>()?;
+
+        // Only a definite UNSAT relaxes the preferences; an interrupt is final
+        let mut result = backend(solver.solve_assumps(&assumptions))?;
+        if result == SolverResult::Unsat {
+            result = backend(solver.solve())?;
+        }
+        let sol = model_or_error(&mut solver, result)?;
+        Ok(self.extract_grid(&model, &sol))
+    }
+}
+
+impl SatSudokuSolver {
+    /// Exact candidates: `[row][col][digit - 1]` is true when some solution
+    /// puts `digit` in the cell.
+    ///
+    /// Each still-open literal costs one incremental solve under a single
+    /// assumption, so this is far slower than `solve`; every model found also
+    /// marks all the literals it sets, which skips most of those calls.
+    pub fn candidate_cells_from_sat(
+        &mut self,
+        puzzle: &[[usize; 9]; 9],
+    ) -> Result<[[[bool; 9]; 9]; 9], SolveError> {
+        let (model, mut solver) = self.load(puzzle)?;
+        let mut feasible = [[[false; 9]; 9]; 9];
+        for row in 0..9 {
+            for col in 0..9 {
+                for digit in 1..=9 {
+                    if feasible[row][col][digit - 1] {
+                        continue;
+                    }
+                    let lit = model.literals[row][col][digit - 1];
+                    let result = backend(solver.solve_assumps(&[lit]))?;
+                    let sol = match model_or_error(&mut solver, result) {
+                        Ok(sol) => sol,
+                        Err(SolveError::Unsatisfiable) => continue,
+                        Err(e) => return Err(e),
+                    };
+                    for (r, c, d) in (0..729).map(|i| (i / 81, i / 9 % 9, i % 9 + 1)) {
+                        if sol[model.literals[r][c][d - 1].var()] == TernaryVal::True {
+                            feasible[r][c][d - 1] = true;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(feasible)
+    }
+}
+
+/// Weighted MaxSAT form of repairing `puzzle`: the classic rules are hard and
+/// every given is a soft unit clause of weight 1, so an optimum keeps as many
+/// givens as possible. `OptInstance::write_dimacs` turns it into WCNF for an
+/// external MaxSAT solver.
+pub fn repair_instance(puzzle: &[[usize; 9]; 9]) -> Result<OptInstance, SolveError> {
+    check_digits(puzzle)?;
+    let mut model = SudokuSat::new();
+    add_minimal_sudoku_constraints(&mut model, Variant::Classic);
+    let mut objective = Objective::new();
+    for (row, col, digit) in givens(puzzle) {
+        objective.add_soft_clause(1, clause![model.literals[row][col][digit - 1]]);
+    }
+    Ok(OptInstance::compose(model.instance, objective))
+}
+
+/// Fewest givens to change so that `puzzle` becomes solvable, and the solution
+/// reached that way.
+///
+/// This solves `repair_instance` with the bundled backend by linear search
+/// from below: a totalizer counts dropped givens, and `dropped <= k` is
+/// assumed for k = 0, 1, ... until the backend finds a model, which is then
+/// optimal. A solvable puzzle returns `(0, solution)` on the first call.
+pub fn repair_fewest_changes(
+    puzzle: &[[usize; 9]; 9],
+) -> Result<(usize, [[usize; 9]; 9]), SolveError> {
+    check_digits(puzzle)?;
+    let mut model = SudokuSat::new();
+    add_minimal_sudoku_constraints(&mut model, Variant::Classic);
+    let dropped: Vec<Lit> = givens(puzzle)
+        .map(|(row, col, digit)| !model.literals[row][col][digit - 1])
+        .collect();
+
+    let (mut cnf, mut vars) = model.instance.clone().into_cnf();
+    let mut totalizer: Totalizer = dropped.iter().copied().collect();
+    backend(totalizer.encode_ub(0..=dropped.len(), &mut cnf, &mut vars))?;
+    let mut solver = CaDiCaL::default();
+    backend(solver.add_cnf(cnf))?;
+
+    for bound in 0..=dropped.len() {
+        let assumptions = backend(totalizer.enforce_ub(bound))?;
+        let result = backend(solver.solve_assumps(&assumptions))?;
+        match model_or_error(&mut solver, result) {
+            Ok(sol) => {
+                let (grid, _) = read_model(&model, &sol);
+                let changes = givens(puzzle)
+                    .filter(|&(row, col, digit)| grid[row][col] != digit)
+                    .count();
+                return Ok((changes, grid));
+            }
+            Err(SolveError::Unsatisfiable) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    // Dropping every given leaves an empty grid, which always has a solution
+    Err(SolveError::Unsatisfiable)
+}
+
+/// A minimal set of givens that together rule out every solution, as 0-indexed
+/// `(row, col)` in row-major order, or `None` if `puzzle` is solvable.
+///
+/// The givens are passed to the backend as assumptions and its core is shrunk
+/// one given at a time, keeping each removal that stays unsatisfiable, so
+/// dropping any single cell of the result makes the rest solvable.
+pub fn explain_unsat(puzzle: &[[usize; 9]; 9]) -> Result<Option<Vec<(usize, usize)>>, SolveError> {
+    check_digits(puzzle)?;
+    let mut model = SudokuSat::new();
+    add_minimal_sudoku_constraints(&mut model, Variant::Classic);
+    let mut solver = CaDiCaL::default();
+    backend(solver.add_cnf(model.instance.into_cnf().0))?;
+
+    let clues: Vec<Lit> = givens(puzzle)
+        .map(|(row, col, digit)| model.literals[row][col][digit - 1])
+        .collect();
+    let mut core = match backend(solver.solve_assumps(&clues))? {
+        SolverResult::Sat => return Ok(None),
+        SolverResult::Unsat => backend(solver.core())?,
+        SolverResult::Interrupted => return Err(SolveError::Interrupted),
+    };
+    // The backend reports the core as negated assumptions
+    for lit in &mut core {
+        *lit = !*lit;
+    }
+
+    let mut i = 0;
+    while i < core.len() {
+        let mut rest = core.clone();
+        rest.remove(i);
+        match backend(solver.solve_assumps(&rest))? {
+            SolverResult::Sat => i += 1,
+            SolverResult::Unsat => core = rest,
+            SolverResult::Interrupted => return Err(SolveError::Interrupted),
+        }
+    }
+
+    let mut cells: Vec<(usize, usize)> = core
+        .iter()
+        .map(|lit| (lit.var().idx() / 81, lit.var().idx() / 9 % 9))
+        .collect();
+    cells.sort_unstable();
+    Ok(Some(cells))
+}
+
+/// Filled cells of a puzzle as `(row, col, digit)`
+fn givens(puzzle: &[[usize; 9]; 9]) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(row, col)| puzzle[row][col] != 0)
+        .map(|(row, col)| (row, col, puzzle[row][col]))
+}
+
+/// The literal for placing `digit` (1-9) in the 0-indexed cell, checked so a
+/// bad caller-supplied placement is an error rather than an index panic
+fn placement_literal(
+    model: &SudokuSat,
+    row: usize,
+    col: usize,
+    digit: usize,
+) -> Result<Lit, SolveError> {
+    if row >= 9 || col >= 9 || !(1..=9).contains(&digit) {
+        return Err(SolveError::InvalidPlacement { row, col, digit });
+    }
+    Ok(model.literals[row][col][digit - 1])
+}
+
+/// The model after a SAT answer; UNSAT and an interrupted search are kept apart
+/// so a timeout is never reported as "no solution"
+fn model_or_error(
+    solver: &mut CaDiCaL<'_, '_>,
+    result: SolverResult,
+) -> Result<Assignment, SolveError> {
+    match result {
+        SolverResult::Sat => backend(solver.full_solution()),
+        SolverResult::Unsat => Err(SolveError::Unsatisfiable),
+        SolverResult::Interrupted => Err(SolveError::Interrupted),
+    }
+}
+
+/// Surface a backend failure as a solve error instead of panicking
+fn backend<T, E: std::fmt::Display>(result: Result<T, E>) -> Result<T, SolveError> {
+    result.map_err(|e| SolveError::SolverBackendError(e.to_string()))
+}
+
+/// Name and version of the SAT backend, as reported by the solver itself
+pub fn backend_signature() -> &'static str {
+    CaDiCaL::default().signature()
+}
+
+/// Variable of the "cell `(row, col)` holds `digit`" literal (0-indexed cell)
+pub fn cell_var(row: usize, col: usize, digit: usize) -> Var {
+    Var::new((row * 81 + col * 9 + digit - 1) as u32)
+}
+
+/// `R{row}C{col}={digit}` label (1-indexed) of an encoding variable
+pub fn var_label(var: Var) -> Option<String> {
+    let i = var.idx();
+    (i < 729).then(|| format!("R{}C{}={}", i / 81 + 1, i / 9 % 9 + 1, i % 9 + 1))
+}
+
+impl SatSudokuSolver {
+    /// Solve and also return the backend's full model, for inspecting the
+    /// literals behind the chosen digits (see `cell_var`)
+    pub fn solve_with_assignment(
+        &mut self,
+        puzzle: &[[usize; 9]; 9],
+    ) -> Result<([[usize; 9]; 9], Assignment), SolveError> {
+        self.last_times = None;
+        let start = self.record_times.then(Instant::now);
+        let (model, mut solver) = self.load(puzzle)?;
+
+        let encoded = self.record_times.then(Instant::now);
+        let result = backend(solver.solve())?;
+        if let (Some(start), Some(encoded)) = (start, encoded) {
+            self.last_times = Some(SolveTimes {
+                encode_time: encoded - start,
+                solve_time: encoded.elapsed(),
+            });
+        }
+        let sol = model_or_error(&mut solver, result)?;
+        Ok((self.extract_grid(&model, &sol), sol))
+    }
+}
+
+impl SudokuSolver for SatSudokuSolver {
+    fn solve(&mut self, puzzle: &[[usize; 9]; 9]) -> Result<[[usize; 9]; 9], SolveError> {
+        self.last_times = None;
+        // A full grid needs no encoding, only a check
+        if let Some(result) = solve_complete(puzzle, self.variant) {
+            return result;
+        }
+        self.solve_with_assignment(puzzle).map(|(grid, _)| grid)
+    }
+
+    fn is_satisfiable(&mut self, puzzle: &[[usize; 9]; 9]) -> Result<bool, SolveError> {
+        if let Some(result) = solve_complete(puzzle, self.variant) {
+            return satisfiable(result);
+        }
+        let (_, mut solver) = self.load(puzzle)?;
+        match backend(solver.solve())? {
+            SolverResult::Sat => Ok(true),
+            SolverResult::Unsat => Ok(false),
+            SolverResult::Interrupted => Err(SolveError::Interrupted),
         }
     }
 }
@@ -43,8 +741,9 @@ impl SudokuSat {
 
         for row in 0..9 {
             for col in 0..9 {
-                for _digit in 1..=9 {
+                for digit in 1..=9 {
                     let lit = instance.new_lit();
+                    debug_assert_eq!(lit.var(), cell_var(row, col, digit));
                     literals[row][col].push(lit);
                 }
             }
@@ -54,7 +753,44 @@ impl SudokuSat {
     }
 }
 
-fn add_puzzle_clues(sudoku: &mut SudokuSat, clue: &[[usize; 9]; 9]) {
+fn build_model(puzzle: &[[usize; 9]; 9], variant: Variant) -> SudokuSat {
+    let mut model = SudokuSat::new();
+    add_minimal_sudoku_constraints(&mut model, variant);
+    add_puzzle_clues(&mut model, puzzle);
+    model
+}
+
+/// The classic Sudoku encoding of `puzzle`, for embedding in a larger problem.
+///
+/// `literals[row][col][digit - 1]` (0-indexed cell) is true when the cell
+/// holds `digit`; its variable is `cell_var(row, col, digit)`. The first 729
+/// variables belong to the encoding, so new variables added to the returned
+/// instance won't collide with it. Constraints are at-least-one digit per cell
+/// and at-most-once per row, column and box, plus unit clauses for the clues.
+pub fn build_instance(puzzle: &[[usize; 9]; 9]) -> (SatInstance, Vec<Vec<Vec<Lit>>>) {
+    let model = build_model(puzzle, Variant::Classic);
+    (model.instance, model.literals)
+}
+
+/// The literals returned by `build_instance` labelled with their meaning, as
+/// `(lit, row, col, digit)` with a 0-indexed cell and `digit` 1-9. All 729
+/// come in variable order, so each label appears exactly once.
+pub fn labeled_literals(
+    literals: &[Vec<Vec<Lit>>],
+) -> impl Iterator<Item = (Lit, usize, usize, usize)> + '_ {
+    literals.iter().enumerate().flat_map(|(row, cols)| {
+        cols.iter().enumerate().flat_map(move |(col, digits)| {
+            digits
+                .iter()
+                .enumerate()
+                .map(move |(i, &lit)| (lit, row, col, i + 1))
+        })
+    })
+}
+
+/// Returns the number of unit clauses added
+fn add_puzzle_clues(sudoku: &mut SudokuSat, clue: &[[usize; 9]; 9]) -> usize {
+    let before = sudoku.instance.n_clauses();
     for row in 0..9 {
         for col in 0..9 {
             let digit = clue[row][col];
@@ -63,6 +799,7 @@ fn add_puzzle_clues(sudoku: &mut SudokuSat, clue: &[[usize; 9]; 9]) {
             }
         }
     }
+    sudoku.instance.n_clauses() - before
 }
 
 fn set_cell(sudoku: &mut SudokuSat, row: usize, col: usize, digit: usize) {
@@ -72,9 +809,18 @@ fn set_cell(sudoku: &mut SudokuSat, row: usize, col: usize, digit: usize) {
         .add_unit(sudoku.literals[row][col][digit - 1]);
 }
 
-fn add_minimal_sudoku_constraints(sudoku: &mut SudokuSat) {
+/// Returns the clause counts per category; `vars` and `clues` are left at zero
+fn add_minimal_sudoku_constraints(sudoku: &mut SudokuSat, variant: Variant) -> EncodingInfo {
     let instance = &mut sudoku.instance;
     let literals = &sudoku.literals;
+    let mut info = EncodingInfo::default();
+    let mut mark = instance.n_clauses();
+    let mut added = |instance: &SatInstance| {
+        let n = instance.n_clauses();
+        let delta = n - mark;
+        mark = n;
+        delta
+    };
 
     // Each cell must contain at least one digit
     for row in 0..9 {
@@ -83,6 +829,7 @@ fn add_minimal_sudoku_constraints(sudoku: &mut SudokuSat) {
             instance.add_clause(clause);
         }
     }
+    info.cell_at_least_one = added(instance);
 
     // Each number appears at most once in each row
     for row in 0..9 {
@@ -99,6 +846,8 @@ fn add_minimal_sudoku_constraints(sudoku: &mut SudokuSat) {
         }
     }
 
+    info.row_amo = added(instance);
+
     // Each number appears at most once in each column
     for col in 0..9 {
         for digit in 1..=9 {
@@ -114,47 +863,148 @@ fn add_minimal_sudoku_constraints(sudoku: &mut SudokuSat) {
         }
     }
 
+    info.col_amo = added(instance);
+
+    if !variant.has_boxes() {
+        return info;
+    }
+
     // Each number appears at most once in each 3x3 sub-grid
     for digit in 1..=9 {
-        for box_row in 0..3 {
-            for box_col in 0..3 {
-                let mut cells = Vec::with_capacity(9);
-                for r in 0..3 {
-                    for c in 0..3 {
-                        let row = box_row * 3 + r;
-                        let col = box_col * 3 + c;
-                        cells.push((row, col));
-                    }
+        for b in 0..9 {
+            let cells = box_cells(b);
+            for i in 0..cells.len() {
+                for j in (i + 1)..cells.len() {
+                    let (row1, col1) = cells[i];
+                    let (row2, col2) = cells[j];
+                    let clause = clause!(
+                        !literals[row1][col1][digit - 1],
+                        !literals[row2][col2][digit - 1]
+                    );
+                    instance.add_clause(clause);
                 }
+            }
+        }
+    }
+    info.box_amo = added(instance);
+    info
+}
 
-                for i in 0..cells.len() {
-                    for j in (i + 1)..cells.len() {
-                        let (row1, col1) = cells[i];
-                        let (row2, col2) = cells[j];
-                        let clause = clause!(
-                            !literals[row1][col1][digit - 1],
-                            !literals[row2][col2][digit - 1]
-                        );
-                        instance.add_clause(clause);
-                    }
+/// Returns the number of clauses added
+fn add_redundant_clauses(
+    sudoku: &mut SudokuSat,
+    redundancy: Redundancy,
+    variant: Variant,
+) -> usize {
+    let instance = &mut sudoku.instance;
+    let literals = &sudoku.literals;
+    let before = instance.n_clauses();
+
+    if redundancy.cell_amo {
+        for cell in literals.iter().flatten() {
+            for d1 in 0..9 {
+                for d2 in (d1 + 1)..9 {
+                    instance.add_clause(clause!(!cell[d1], !cell[d2]));
                 }
             }
         }
     }
+
+    // `UNITS` holds the rows, then the columns, then the boxes
+    let families = [
+        redundancy.row_alo,
+        redundancy.col_alo,
+        redundancy.box_alo && variant.has_boxes(),
+    ];
+    for (family, units) in families.into_iter().zip(UNITS.chunks(9)) {
+        if !family {
+            continue;
+        }
+        for unit in units {
+            for digit in 1..=9 {
+                let clause = unit
+                    .iter()
+                    .map(|&(r, c)| literals[r][c][digit - 1])
+                    .collect();
+                instance.add_clause(clause);
+            }
+        }
+    }
+    instance.n_clauses() - before
+}
+
+/// Cells of a model that don't hold exactly one digit
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModelError {
+    /// 0-indexed `(row, col, digits set true)`; the digit list is empty or has
+    /// more than one entry
+    pub cells: Vec<(usize, usize, Vec<usize>)>,
+}
+
+impl std::fmt::Display for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "model is not a grid:")?;
+        for (i, (row, col, digits)) in self.cells.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{}R{}C{} ", sep, row + 1, col + 1)?;
+            if digits.is_empty() {
+                write!(f, "has no digit")?;
+            } else {
+                write!(f, "has digits {:?}", digits)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// Digits whose literal `model` sets true in one cell
+fn true_digits(model: &Assignment, cell: &[Lit]) -> Vec<usize> {
+    (1..=9)
+        .filter(|&d| model.lit_value(cell[d - 1]) == TernaryVal::True)
+        .collect()
 }
 
-fn extract_grid(sudoku: &SudokuSat, sol: &Assignment) -> [[usize; 9]; 9] {
+/// Read a grid from `model`, failing unless every cell has exactly one of its
+/// `literals[row][col][digit - 1]` set true
+pub fn assignment_to_grid(
+    model: &Assignment,
+    literals: &[Vec<Vec<Lit>>],
+) -> Result<[[usize; 9]; 9], ModelError> {
     let mut grid = [[0usize; 9]; 9];
+    let mut cells = Vec::new();
     for row in 0..9 {
         for col in 0..9 {
-            for digit in 1..=9 {
-                let lit = sudoku.literals[row][col][digit - 1];
-                if sol[lit.var()] == TernaryVal::True {
-                    grid[row][col] = digit;
-                    break;
+            let digits = true_digits(model, &literals[row][col]);
+            if digits.len() == 1 {
+                grid[row][col] = digits[0];
+            } else {
+                cells.push((row, col, digits));
+            }
+        }
+    }
+    if cells.is_empty() {
+        Ok(grid)
+    } else {
+        Err(ModelError { cells })
+    }
+}
+
+/// Lenient `assignment_to_grid`: a cell takes its lowest true digit, or 0.
+/// The error is returned alongside rather than printed, so the caller can
+/// report it where its output goes (stderr would corrupt the TUI).
+fn read_model(sudoku: &SudokuSat, sol: &Assignment) -> ([[usize; 9]; 9], Option<ModelError>) {
+    match assignment_to_grid(sol, &sudoku.literals) {
+        Ok(grid) => (grid, None),
+        Err(err) => {
+            let mut grid = [[0usize; 9]; 9];
+            for (cells, lits) in grid.iter_mut().zip(&sudoku.literals) {
+                for (cell, lits) in cells.iter_mut().zip(lits) {
+                    *cell = true_digits(sol, lits).first().copied().unwrap_or(0);
                 }
             }
+            (grid, Some(err))
         }
     }
-    grid
 }
@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+/// How long a partly typed value waits for its next digit before it is dropped
+pub const ENTRY_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Digits typed towards one cell value.
+///
+/// A value commits as soon as no further digit could extend it to a legal
+/// value, so with at most 9 every keypress commits at once and entry stays
+/// instant. Larger grids (values up to 16) buffer a leading `1` until the next
+/// digit, Enter, or Esc; see `push`.
+pub struct EntryBuffer {
+    /// Largest value a cell can hold: 9, or 16 for 16x16
+    max: usize,
+    /// Value typed so far and when its first digit came in
+    pending: Option<(usize, Instant)>,
+}
+
+/// What a keypress did to the buffer
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Entry {
+    /// Waiting for another digit, Enter, or Esc
+    Pending(usize),
+    /// Set the cell to this value (0 clears it)
+    Commit(usize),
+    /// The digits typed make a value above `max`; the buffer is emptied
+    Rejected(usize),
+}
+
+impl EntryBuffer {
+    pub fn new(max: usize) -> Self {
+        EntryBuffer { max, pending: None }
+    }
+
+    /// Add a typed digit; an expired value is discarded first
+    pub fn push(&mut self, digit: usize, now: Instant) -> Entry {
+        let value = match self.pending(now) {
+            Some(value) => value * 10 + digit,
+            None => digit,
+        };
+        self.pending = None;
+        if value > self.max {
+            Entry::Rejected(value)
+        } else if value == 0 || value * 10 > self.max {
+            Entry::Commit(value)
+        } else {
+            self.pending = Some((value, now));
+            Entry::Pending(value)
+        }
+    }
+
+    /// Enter: the value typed so far, if any
+    pub fn commit(&mut self, now: Instant) -> Option<usize> {
+        let value = self.pending(now);
+        self.pending = None;
+        value
+    }
+
+    /// Esc: drop whatever was typed; returns whether anything was pending
+    pub fn cancel(&mut self) -> bool {
+        self.pending.take().is_some()
+    }
+
+    /// The value being typed, for the pending-entry indicator
+    pub fn pending(&self, now: Instant) -> Option<usize> {
+        self.pending
+            .filter(|&(_, started)| now.duration_since(started) < ENTRY_TIMEOUT)
+            .map(|(value, _)| value)
+    }
+
+    /// Time left before a pending value expires, so the event loop can wake
+    /// up to clear its indicator
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.pending
+            .map(|(_, started)| ENTRY_TIMEOUT.saturating_sub(now.duration_since(started)))
+            .filter(|left| !left.is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits_commit_at_once_up_to_nine() {
+        let mut entry = EntryBuffer::new(9);
+        let now = Instant::now();
+        for d in 0..=9 {
+            assert_eq!(entry.push(d, now), Entry::Commit(d));
+        }
+        assert_eq!(entry.pending(now), None);
+    }
+
+    #[test]
+    fn two_digit_values_wait_for_the_second_digit_or_enter() {
+        let mut entry = EntryBuffer::new(16);
+        let now = Instant::now();
+        assert_eq!(entry.push(1, now), Entry::Pending(1));
+        assert_eq!(entry.pending(now), Some(1));
+        assert_eq!(entry.push(2, now), Entry::Commit(12));
+
+        assert_eq!(entry.push(1, now), Entry::Pending(1));
+        assert_eq!(entry.commit(now), Some(1));
+        assert_eq!(entry.commit(now), None);
+
+        // Nothing above 16 starts with 2-9
+        assert_eq!(entry.push(7, now), Entry::Commit(7));
+        assert_eq!(entry.push(1, now), Entry::Pending(1));
+        assert_eq!(entry.push(7, now), Entry::Rejected(17));
+        assert_eq!(entry.pending(now), None);
+    }
+
+    #[test]
+    fn escape_and_the_timeout_drop_a_pending_value() {
+        let mut entry = EntryBuffer::new(16);
+        let now = Instant::now();
+        entry.push(1, now);
+        assert!(entry.cancel());
+        assert!(!entry.cancel());
+
+        entry.push(1, now);
+        assert_eq!(entry.remaining(now), Some(ENTRY_TIMEOUT));
+        let later = now + ENTRY_TIMEOUT;
+        assert_eq!(entry.pending(later), None);
+        assert_eq!(entry.remaining(later), None);
+        // A fresh digit after the timeout starts a new value
+        assert_eq!(entry.push(6, later), Entry::Commit(6));
+    }
+}
@@ -1,5 +1,6 @@
 use crossterm::ExecutableCommand;
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::cursor::Show;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
@@ -9,10 +10,31 @@ use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph, Tabs};
-use std::io::{self, stdout};
+use std::cell::Cell;
+use std::io::{self, Stdout, Write, stdout};
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::sync::{Arc, mpsc};
 use std::time::{Duration, Instant};
 
-use crate::solver::{SolverKind, SudokuSolver, make_solver};
+mod entry;
+mod palette;
+
+use crate::grid::{
+    ErrorKind, box_index, conflicting_cells, diff, digit_counts, find_duplicates, first_error,
+    flip_horizontal, flip_vertical, givens_mask, has_conflicts, is_solved, respects_givens,
+    rotate_ccw, rotate_cw, satisfaction,
+};
+use crate::io::Puzzle;
+use crate::puzzles::{default_puzzle, sample_puzzle};
+use crate::solver::backtracking::{TwoSolutions, count_solutions_until, find_two_solutions};
+use crate::solver::logic::{
+    Candidates, Difficulty, RatingCache, SolveStep, best_hint, estimate_difficulty, find_dead_cell,
+    naked_forced, next_step,
+};
+use crate::solver::sat::explain_unsat;
+use crate::solver::{SolveError, SolverKind, SudokuSolver, make_solver, panic_message};
+use entry::{Entry, EntryBuffer};
+use palette::{Action, Palette, PaletteOutcome, action_for_key};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum Focus {
@@ -20,46 +42,436 @@ enum Focus {
     Grid,
 }
 
+/// Whether the grid is being set up or played
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Phase {
+    /// Every cell is editable and every filled cell counts as a clue
+    EditClues,
+    /// The clues are locked; entries are the player's
+    Play,
+}
+
+/// Characters used for the grid lines: heavy between 3x3 boxes, light
+/// between the cells of a box when those are switched on
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum BorderStyle {
+    Ascii,
+    Unicode,
+}
+
+impl BorderStyle {
+    /// Unicode when the locale advertises UTF-8, unless `SUDOKU_ASCII` is set
+    fn detect() -> Self {
+        if std::env::var_os("SUDOKU_ASCII").is_some() {
+            return BorderStyle::Ascii;
+        }
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|k| std::env::var(k).ok())
+            .find(|v| !v.is_empty())
+            .unwrap_or_default()
+            .to_uppercase();
+        if locale.contains("UTF-8") || locale.contains("UTF8") {
+            BorderStyle::Unicode
+        } else {
+            BorderStyle::Ascii
+        }
+    }
+
+    fn toggled(self) -> Self {
+        match self {
+            BorderStyle::Ascii => BorderStyle::Unicode,
+            BorderStyle::Unicode => BorderStyle::Ascii,
+        }
+    }
+
+    /// Horizontal line between cell rows, heavy between bands of boxes and
+    /// light inside them, as wide as a grid row of `order²` cells
+    /// `cell_width` characters wide. Box-drawing characters are single-width,
+    /// so both styles line up the same
+    fn separator_row(
+        self,
+        order: usize,
+        cell_width: usize,
+        heavy: bool,
+        inner_lines: bool,
+    ) -> String {
+        let dash = match (self, heavy) {
+            (BorderStyle::Ascii, true) => '-',
+            (BorderStyle::Ascii, false) => '.',
+            (BorderStyle::Unicode, true) => '━',
+            (BorderStyle::Unicode, false) => '─',
+        };
+        let mut row = String::new();
+        for c in 0..order * order {
+            // Under a column separator: its bar, then its padding space
+            if let Some(bar_heavy) = line_before(order, c, inner_lines) {
+                row.push(self.crossing(heavy, bar_heavy));
+                row.push(dash);
+            }
+            row.extend(std::iter::repeat_n(dash, cell_width));
+        }
+        // Rows end with the last cell's padding space, which needs no line
+        row.pop();
+        row
+    }
+
+    /// Where a horizontal line meets a vertical one of the given weights
+    fn crossing(self, row_heavy: bool, col_heavy: bool) -> char {
+        match (self, row_heavy, col_heavy) {
+            (BorderStyle::Ascii, true, true) => '+',
+            (BorderStyle::Ascii, true, false) => '-',
+            (BorderStyle::Ascii, false, true) => '|',
+            (BorderStyle::Ascii, false, false) => '.',
+            (BorderStyle::Unicode, true, true) => '╋',
+            (BorderStyle::Unicode, true, false) => '┿',
+            (BorderStyle::Unicode, false, true) => '╂',
+            (BorderStyle::Unicode, false, false) => '┼',
+        }
+    }
+
+    fn column_separator(self, heavy: bool) -> &'static str {
+        match (self, heavy) {
+            (BorderStyle::Ascii, true) => "| ",
+            (BorderStyle::Ascii, false) => ": ",
+            (BorderStyle::Unicode, true) => "┃ ",
+            (BorderStyle::Unicode, false) => "│ ",
+        }
+    }
+}
+
+/// Line before row or column `i` of a grid with box order `order`:
+/// `Some(true)` for a heavy box boundary, `Some(false)` for a light line
+/// between cells of a box (only with `inner_lines`), `None` for no line
+fn line_before(order: usize, i: usize, inner_lines: bool) -> Option<bool> {
+    if i == 0 {
+        None
+    } else if i.is_multiple_of(order) {
+        Some(true)
+    } else {
+        inner_lines.then_some(false)
+    }
+}
+
+/// How much colour the terminal can show; styles fall back to bold, underline
+/// and reverse video so the cursor and highlights stay visible without it
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum ColorSupport {
+    /// 256 colours or more
+    Full,
+    /// The 16 ANSI colours
+    Basic,
+    Monochrome,
+}
+
+impl ColorSupport {
+    /// From `NO_COLOR`, `COLORTERM` and `TERM`, in that order of precedence
+    fn detect() -> Self {
+        let var = |k| std::env::var(k).unwrap_or_default();
+        if !var("NO_COLOR").is_empty() {
+            return ColorSupport::Monochrome;
+        }
+        if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            return ColorSupport::Full;
+        }
+        match var("TERM").as_str() {
+            "" | "dumb" => ColorSupport::Monochrome,
+            term if term.contains("256color") => ColorSupport::Full,
+            _ => ColorSupport::Basic,
+        }
+    }
+
+    fn cursor(self) -> Style {
+        match self {
+            ColorSupport::Monochrome => {
+                Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+            }
+            _ => Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Selected tab, palette prompt and help headings
+    fn accent(self) -> Style {
+        match self {
+            ColorSupport::Monochrome => Style::default().add_modifier(Modifier::UNDERLINED),
+            _ => Style::default().fg(Color::Yellow),
+        }
+    }
+
+    /// Layered onto a clue blamed for a failed solve
+    fn conflict(self) -> Style {
+        match self {
+            ColorSupport::Monochrome => {
+                Style::default().add_modifier(Modifier::BOLD | Modifier::CROSSED_OUT)
+            }
+            _ => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Layered onto the cells that differ between two solutions
+    fn ambiguous(self) -> Style {
+        match self {
+            ColorSupport::Monochrome => Style::default().add_modifier(Modifier::ITALIC),
+            _ => Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Layered onto the cells of the Shift+Arrows selection
+    fn selection(self) -> Style {
+        match self {
+            ColorSupport::Full => Style::default().bg(Color::Indexed(24)),
+            ColorSupport::Basic => Style::default().bg(Color::Blue),
+            ColorSupport::Monochrome => Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    /// Layered onto the cells sharing a unit with the cursor
+    fn peer(self) -> Style {
+        match self {
+            ColorSupport::Full => Style::default().bg(Color::Indexed(236)),
+            // Dark grey is already taken by candidate spots
+            _ => Style::default().add_modifier(Modifier::DIM),
+        }
+    }
+
+    /// Layered onto cells holding the highlighted digit
+    fn highlight(self) -> Style {
+        match self {
+            ColorSupport::Monochrome => {
+                Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            }
+            _ => Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Empty cells that can still take the highlighted digit
+    fn candidate_spot(self) -> Style {
+        match self {
+            ColorSupport::Monochrome => Style::default().add_modifier(Modifier::UNDERLINED),
+            _ => Style::default().bg(Color::DarkGray),
+        }
+    }
+
+    fn ruler(self) -> Style {
+        match self {
+            ColorSupport::Monochrome => Style::default().add_modifier(Modifier::DIM),
+            _ => Style::default().fg(Color::DarkGray),
+        }
+    }
+}
+
+/// Grid state restored by undo
+#[derive(Copy, Clone)]
+struct Snapshot {
+    grid: [[usize; 9]; 9],
+    givens: [[bool; 9]; 9],
+}
+
+/// Undo steps kept before the oldest is dropped
+const UNDO_LIMIT: usize = 100;
+
 struct App {
+    /// Change only through `set_grid` and `set_digit`, which keep `cands` in step
     grid: [[usize; 9]; 9],
+    /// Candidates of `grid`, updated on every edit instead of once per frame
+    cands: Candidates,
+    /// `(row, col, digit)` candidates ruled out by `n` elimination steps, hidden
+    /// from the pencil marks; dropped once a filled cell changes
+    ruled_out: Vec<(usize, usize, usize)>,
+    /// Cells that belong to the puzzle itself and can't be edited while playing
+    givens: [[bool; 9]; 9],
+    phase: Phase,
+    /// States before each edit, newest last
+    undo: Vec<Snapshot>,
+    /// Draw 1-9 coordinate labels above and left of the grid
+    show_rulers: bool,
+    /// Draw light lines between the cells of each box
+    inner_lines: bool,
+    /// Shade the cells sharing a row, column or box with the cursor
+    shade_peers: bool,
+    /// Move the cursor to the next editable cell after typing a digit
+    auto_advance: bool,
+    /// Digits typed towards the cursor's cell that haven't been committed yet
+    entry: EntryBuffer,
     cursor: (usize, usize),
-    solver_idx: usize, // 0 = SAT, 1 = Backtracking, 2 = ExactCover (not yet implemented)
+    solver_idx: usize, // 0 = SAT, 1 = Backtracking, 2 = Auto, 3 = ExactCover
     focus: Focus,
     message: Option<String>,
     show_help: bool,
     last_solve_time: Option<Duration>,
+    /// Time spent in all finished solves this session, and how many there were
+    session_solve_time: Duration,
+    session_solves: usize,
+    palette: Option<Palette>,
+    borders: BorderStyle,
+    colors: ColorSupport,
+    /// Digit whose cells (and candidate spots) are highlighted
+    highlight_digit: Option<usize>,
+    /// `f` was pressed and the next digit picks the highlight
+    picking_highlight: bool,
+    /// Collection loaded from `--file`, paged with PageUp/PageDown
+    puzzles: Vec<Puzzle>,
+    puzzle_idx: usize,
+    /// For each loaded puzzle, the earlier puzzle it is isomorphic to
+    duplicate_of: Vec<Option<usize>>,
+    /// Difficulty of the current collection puzzle, shown in the title
+    rating: Option<Difficulty>,
+    /// Ratings of visited puzzles; `None` when disabled with `SUDOKU_NO_RATING_CACHE`
+    rating_cache: Option<RatingCache>,
+    /// Clues blamed for the last failed solve, shown in red until the grid changes
+    conflicts: Vec<(usize, usize)>,
+    /// Cells where two solutions of the last solved puzzle differ, marked
+    /// until the grid changes
+    ambiguous: Vec<(usize, usize)>,
+    /// Index into `Difficulty::ALL` of the sample the next `D` loads
+    sample_idx: usize,
+    /// Opposite corners of the rectangle picked with Shift+Arrows: the
+    /// anchor, then the cursor
+    selection: Option<((usize, usize), (usize, usize))>,
+    /// Solve running in the background, shown with a spinner until it lands
+    solving: Option<PendingSolve>,
+    /// Redraw interval while something animates; `SUDOKU_TICK_MS` sets it
+    tick: Duration,
 }
 
 impl Default for App {
     fn default() -> Self {
         Self {
             grid: [[0; 9]; 9],
+            cands: Candidates::from_grid(&[[0; 9]; 9]),
+            ruled_out: Vec::new(),
+            givens: [[false; 9]; 9],
+            phase: Phase::EditClues,
+            undo: Vec::new(),
+            show_rulers: false,
+            inner_lines: false,
+            shade_peers: true,
+            auto_advance: false,
+            entry: EntryBuffer::new(9),
             cursor: (0, 0),
             solver_idx: 0,
             focus: Focus::Grid,
             message: None,
             show_help: true,
             last_solve_time: None,
+            session_solve_time: Duration::ZERO,
+            session_solves: 0,
+            palette: None,
+            borders: BorderStyle::detect(),
+            colors: ColorSupport::detect(),
+            highlight_digit: None,
+            picking_highlight: false,
+            puzzles: Vec::new(),
+            puzzle_idx: 0,
+            duplicate_of: Vec::new(),
+            rating: None,
+            rating_cache: std::env::var_os("SUDOKU_NO_RATING_CACHE")
+                .is_none()
+                .then(|| RatingCache::new(RATING_CACHE_SIZE)),
+            conflicts: Vec::new(),
+            ambiguous: Vec::new(),
+            sample_idx: 0,
+            selection: None,
+            solving: None,
+            tick: std::env::var("SUDOKU_TICK_MS")
+                .ok()
+                .and_then(|ms| ms.parse().ok())
+                .map_or(DEFAULT_TICK, Duration::from_millis),
         }
     }
 }
 
-fn default_puzzle() -> [[usize; 9]; 9] {
-    [
-        [0, 3, 6, 0, 0, 0, 9, 0, 0],
-        [1, 0, 0, 5, 3, 0, 2, 0, 0],
-        [0, 0, 4, 0, 0, 0, 0, 0, 6],
-        [0, 4, 7, 0, 0, 0, 0, 5, 3],
-        [0, 0, 0, 0, 0, 8, 0, 6, 9],
-        [6, 9, 0, 0, 4, 0, 0, 0, 0],
-        [0, 0, 0, 8, 0, 7, 0, 0, 1],
-        [0, 0, 2, 0, 0, 0, 0, 0, 4],
-        [0, 8, 5, 0, 0, 0, 0, 2, 0],
-    ]
+impl App {
+    /// Load collection puzzle `idx` and rate it
+    fn open_collection_puzzle(&mut self, idx: usize) {
+        self.puzzle_idx = idx;
+        let grid = self.puzzles[idx].grid;
+        self.load_puzzle(grid);
+        self.rating = match &mut self.rating_cache {
+            Some(cache) => cache.rating(&grid),
+            None => estimate_difficulty(&grid),
+        };
+    }
+
+    /// Replace the whole grid and recompute its candidates
+    fn set_grid(&mut self, grid: [[usize; 9]; 9]) {
+        self.grid = grid;
+        self.cands = Candidates::from_grid(&grid);
+        self.ruled_out.clear();
+    }
+
+    /// Change one cell, updating only the candidates it can affect.
+    /// Eliminations stay valid while the grid only gains digits.
+    fn set_digit(&mut self, row: usize, col: usize, d: usize) {
+        if self.grid[row][col] != 0 {
+            self.ruled_out.clear();
+        }
+        self.grid[row][col] = d;
+        self.cands.refresh(&self.grid, row, col);
+        debug_assert_eq!(self.cands, Candidates::from_grid(&self.grid));
+    }
+
+    /// `set_digit` for an entry, which while editing clues also makes the
+    /// cell a clue (or, for 0, no longer one)
+    fn enter_digit(&mut self, row: usize, col: usize, d: usize) {
+        if self.phase == Phase::EditClues {
+            self.givens[row][col] = d != 0;
+        }
+        self.set_digit(row, col, d);
+    }
+
+    /// Candidates left after the eliminations, as shown and used by `n`
+    fn pencil_marks(&self) -> Candidates {
+        let mut cands = self.cands;
+        for &(row, col, d) in &self.ruled_out {
+            cands.remove(row, col, d);
+        }
+        cands
+    }
+
+    /// Remember the current grid so the next change can be undone
+    fn push_undo(&mut self) {
+        self.conflicts.clear();
+        self.ambiguous.clear();
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(Snapshot {
+            grid: self.grid,
+            givens: self.givens,
+        });
+    }
+
+    /// Apply the same geometric transform to the grid and its givens
+    fn transform(&mut self, name: &str, grid: [[usize; 9]; 9], givens: [[bool; 9]; 9]) {
+        self.push_undo();
+        self.set_grid(grid);
+        self.givens = givens;
+        self.message = Some(name.to_string());
+    }
+
+    /// Replace the grid with a new puzzle whose filled cells become the givens
+    fn load_puzzle(&mut self, puzzle: [[usize; 9]; 9]) {
+        self.undo.clear();
+        self.conflicts.clear();
+        self.ambiguous.clear();
+        self.set_grid(puzzle);
+        self.givens = givens_mask(&puzzle);
+        self.phase = Phase::Play;
+        self.cursor = (0, 0);
+        self.last_solve_time = None;
+    }
 }
 
 fn solver_titles() -> Vec<Line<'static>> {
-    vec!["SAT", "Backtracking", "ExactCover"]
+    vec!["SAT", "Backtracking", "Auto", "ExactCover"]
         .into_iter()
         .map(|t| Line::from(t.to_string()))
         .collect()
@@ -69,27 +481,108 @@ fn current_solver_kind(idx: usize) -> SolverKind {
     match idx {
         0 => SolverKind::Sat,
         1 => SolverKind::Backtracking,
-        // 2 => SolverKind::ExactCover,
+        2 => SolverKind::Auto,
+        3 => SolverKind::ExactCover,
         _ => SolverKind::Sat,
     }
 }
 
-pub fn run() -> io::Result<()> {
-    // Setup terminal in raw mode and alternate screen
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    stdout.execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+/// Raw mode and the alternate screen, undone on drop so that a panic or an
+/// early `?` return still hands the shell back a usable terminal
+struct TerminalGuard<W: Write> {
+    out: W,
+    /// Whether raw mode was switched on, and so has to be switched off again
+    raw: bool,
+}
+
+impl TerminalGuard<Stdout> {
+    fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        // From here on, dropping the guard undoes whatever was set up
+        let mut guard = TerminalGuard {
+            out: stdout(),
+            raw: true,
+        };
+        guard.out.execute(EnterAlternateScreen)?;
+        Ok(guard)
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        restore_terminal(&mut self.out, self.raw);
+    }
+}
+
+/// Leave the alternate screen, switch raw mode off if `raw`, and show the
+/// cursor. Errors are ignored: there is nothing better to do while tearing
+/// down, and doing it twice is harmless.
+fn restore_terminal(out: &mut impl Write, raw: bool) {
+    let _ = out.execute(LeaveAlternateScreen);
+    if raw {
+        let _ = disable_raw_mode();
+    }
+    let _ = out.execute(Show);
+}
+
+thread_local! {
+    /// Set while `solve_caught` runs, so the panic hook leaves the screen to
+    /// the TUI for a panic that ends up in the status bar
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+type PanicHook = dyn Fn(&PanicHookInfo<'_>) + Send + Sync;
+
+/// Panic hook that restores the terminal before `previous` prints the
+/// message. The guard would restore it too, but only while unwinding, after
+/// the message was printed to the alternate screen and then wiped with it.
+fn restoring_panic_hook(previous: Arc<PanicHook>) -> Box<PanicHook> {
+    Box::new(move |info| {
+        if !CATCHING.get() {
+            restore_terminal(&mut stdout(), true);
+            previous(info);
+        }
+    })
+}
+
+/// Run a solve, turning a panic into a `SolverBackendError` so a broken
+/// backend can't take the terminal down; the message goes to the status bar
+fn solve_caught(
+    solve: impl FnOnce() -> Result<[[usize; 9]; 9], SolveError>,
+) -> Result<[[usize; 9]; 9], SolveError> {
+    CATCHING.set(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(solve));
+    CATCHING.set(false);
+    result.unwrap_or_else(|payload| {
+        let msg = format!("panicked: {}", panic_message(&*payload));
+        Err(SolveError::SolverBackendError(msg))
+    })
+}
+
+/// Start the TUI; `puzzles` (possibly empty) can be paged through with PageUp/PageDown
+pub fn run(puzzles: Vec<Puzzle>) -> io::Result<()> {
+    let previous_hook: Arc<PanicHook> = Arc::from(panic::take_hook());
+    panic::set_hook(restoring_panic_hook(previous_hook.clone()));
+    // Declared before the terminal so it is dropped after it
+    let guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
 
     let mut app = App::default();
+    let grids: Vec<_> = puzzles.iter().map(|p| p.grid).collect();
+    app.duplicate_of = find_duplicates(&grids);
+    app.puzzles = puzzles;
+    if !app.puzzles.is_empty() {
+        app.open_collection_puzzle(0);
+        app.show_help = false;
+    }
 
     let res = run_app(&mut terminal, &mut app);
 
-    // Restore terminal
-    terminal.backend_mut().execute(LeaveAlternateScreen)?;
-    disable_raw_mode()?;
-    terminal.show_cursor()?;
+    // Restore the terminal before reporting anything
+    drop(terminal);
+    drop(guard);
+    panic::set_hook(Box::new(move |info| previous_hook(info)));
 
     // Propagate error if any
     if let Err(e) = res {
@@ -104,78 +597,626 @@ fn run_app(
     app: &mut App,
 ) -> io::Result<()> {
     loop {
+        poll_solve(app);
         terminal.draw(|f| ui(f, app))?;
 
-        if event::poll(std::time::Duration::from_millis(250))? {
-            if let Event::Key(key) = event::read()? {
-                if handle_key(app, key)? {
-                    break; // exit
-                }
-            }
+        // Tick fast only while a background solve's spinner turns, and slowly
+        // otherwise, when nothing on screen moves by itself. Input ends the
+        // wait at once either way, and a pending multi-digit value wakes the
+        // loop when it times out so its indicator goes.
+        let mut wait = if app.solving.is_some() {
+            app.tick
+        } else {
+            IDLE_TICK
+        };
+        if let Some(left) = app.entry.remaining(Instant::now()) {
+            wait = wait.min(left);
+        }
+        if !event::poll(wait)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && handle_key(app, key)?
+        {
+            break; // exit
         }
     }
     Ok(())
 }
 
 fn handle_key(app: &mut App, key: KeyEvent) -> io::Result<bool> {
+    // A running solve would overwrite any edit, so only quitting works until
+    // it lands
+    if app.solving.is_some() {
+        return Ok(action_for_key(key.code) == Some(Action::Quit));
+    }
+
+    // While the palette is open it owns the keyboard
+    if let Some(palette) = app.palette.as_mut() {
+        match palette.handle_key(key.code) {
+            PaletteOutcome::Open => {}
+            PaletteOutcome::Close => app.palette = None,
+            PaletteOutcome::Run(action) => {
+                app.palette = None;
+                return Ok(run_action(app, action));
+            }
+        }
+        return Ok(false);
+    }
+
+    if app.picking_highlight {
+        app.picking_highlight = false;
+        match key.code {
+            KeyCode::Char(ch @ '1'..='9') => {
+                app.highlight_digit = ch.to_digit(10).map(|d| d as usize);
+                app.message = Some(format!("Highlighting {}", ch));
+            }
+            _ => {
+                app.highlight_digit = None;
+                app.message = None;
+            }
+        }
+        return Ok(false);
+    }
+
     // When help is visible, only toggle/close help or quit
     if app.show_help {
         match key.code {
-            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('h') => {
-                app.show_help = false;
+            KeyCode::Esc => app.show_help = false,
+            code => {
+                if let Some(action @ (Action::ToggleHelp | Action::Quit)) = action_for_key(code) {
+                    return Ok(run_action(app, action));
+                }
             }
-            KeyCode::Char('q') => return Ok(true),
-            _ => {}
         }
         return Ok(false);
     }
 
-    match key.code {
-        KeyCode::Char('q') => return Ok(true),
-        KeyCode::Char('?') | KeyCode::Char('h') => {
-            app.show_help = true;
+    if key.code == KeyCode::Esc && app.highlight_digit.is_some() {
+        app.highlight_digit = None;
+        return Ok(false);
+    }
+
+    match action_for_key(key.code) {
+        Some(action) => return Ok(run_action(app, action)),
+        None => match app.focus {
+            Focus::Grid => handle_grid_keys(app, key),
+            Focus::Solver => handle_solver_keys(app, key),
+        },
+    }
+    Ok(false)
+}
+
+/// Perform an action; returns true when the app should exit
+fn run_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Quit => return true,
+        Action::ToggleHelp => app.show_help = !app.show_help,
+        Action::OpenPalette => {
+            app.show_help = false;
+            app.palette = Some(Palette::default());
         }
-        KeyCode::Char('d') => {
-            app.grid = default_puzzle();
+        Action::LoadDefault => {
+            app.load_puzzle(default_puzzle());
             app.message = Some("Loaded default puzzle".into());
-            app.cursor = (0, 0);
         }
-        KeyCode::Tab => {
+        Action::LoadSample => {
+            let difficulty = Difficulty::ALL[app.sample_idx];
+            app.sample_idx = (app.sample_idx + 1) % Difficulty::ALL.len();
+            app.load_puzzle(sample_puzzle(difficulty));
+            app.message = Some(format!("Loaded {} sample puzzle", difficulty));
+        }
+        Action::ToggleFocus => {
             app.focus = match app.focus {
                 Focus::Grid => Focus::Solver,
                 Focus::Solver => Focus::Grid,
             };
         }
-        KeyCode::Char('s') => {
-            app.message = Some("Solving...".into());
-            let kind = current_solver_kind(app.solver_idx);
-            let mut solver = make_solver(kind);
-            let start = Instant::now();
-            match solver.solve(&app.grid) {
-                Some(sol) => {
-                    app.grid = sol;
-                    let elapsed = start.elapsed();
-                    app.last_solve_time = Some(elapsed);
-                    app.message = Some(format!("Solved in {} ms", elapsed.as_millis()));
+        Action::Solve => {
+            // While playing, solve the puzzle itself and replace the player's entries
+            let clues = match app.phase {
+                Phase::Play => givens_only(app),
+                Phase::EditClues => app.grid,
+            };
+            solve_from(app, &clues, false);
+        }
+        Action::Race => {
+            let clues = match app.phase {
+                Phase::Play => givens_only(app),
+                Phase::EditClues => app.grid,
+            };
+            race(app, &clues);
+        }
+        Action::ContinueSolving => {
+            let clues = app.grid;
+            solve_from(app, &clues, true);
+        }
+        Action::Step => match next_step(&app.grid, &app.pencil_marks()) {
+            Some(step) => {
+                match step {
+                    SolveStep::Place {
+                        row, col, digit, ..
+                    } => {
+                        app.push_undo();
+                        app.enter_digit(row, col, digit);
+                        app.cursor = (row, col);
+                    }
+                    SolveStep::Eliminate {
+                        cell: (row, col),
+                        ref digits,
+                        ..
+                    } => {
+                        app.ruled_out.extend(digits.iter().map(|&d| (row, col, d)));
+                        app.cursor = (row, col);
+                    }
+                    SolveStep::GuessRequired { .. } => {}
                 }
-                None => {
-                    let elapsed = start.elapsed();
-                    app.last_solve_time = Some(elapsed);
-                    app.message = Some(format!("No solution ({} ms)", elapsed.as_millis()));
+                app.message = Some(step.to_string());
+            }
+            None if app.grid.iter().flatten().all(|&d| d != 0) => {
+                app.message = Some("Grid is full".into());
+            }
+            None => app.message = Some("No logical step found; guessing required".into()),
+        },
+        Action::FillObvious => {
+            let forced = naked_forced(&app.grid);
+            if forced.is_empty() {
+                app.message = Some("No obvious cells to fill".into());
+            } else {
+                app.push_undo();
+                for &(row, col, digit) in &forced {
+                    app.enter_digit(row, col, digit);
                 }
+                app.message = Some(format!("Filled {} obvious cells", forced.len()));
             }
         }
-        KeyCode::Char('c') => {
-            app.grid = [[0; 9]; 9];
+        Action::Hint => match best_hint(&app.grid, &app.pencil_marks()) {
+            Some(hint) => {
+                app.cursor = (hint.row, hint.col);
+                app.message = Some(hint.to_string());
+            }
+            None if app.grid.iter().flatten().all(|&d| d != 0) => {
+                app.message = Some("Grid is full".into());
+            }
+            None => app.message = Some("No logical step found; guessing required".into()),
+        },
+        Action::NextPuzzle | Action::PrevPuzzle => {
+            if app.puzzles.is_empty() {
+                app.message = Some("No puzzle collection loaded (use --file)".into());
+                return false;
+            }
+            let n = app.puzzles.len();
+            let idx = match action {
+                Action::NextPuzzle => (app.puzzle_idx + 1).min(n - 1),
+                _ => app.puzzle_idx.saturating_sub(1),
+            };
+            app.open_collection_puzzle(idx);
+            app.message = Some(format!("Puzzle {}/{}", app.puzzle_idx + 1, n));
+        }
+        Action::TogglePhase => match app.phase {
+            Phase::EditClues => {
+                app.givens = givens_mask(&app.grid);
+                app.phase = Phase::Play;
+                let count = app.givens.iter().flatten().filter(|&&g| g).count();
+                app.message = Some(format!("Locked {} clues; playing", count));
+            }
+            Phase::Play => {
+                // Everything on the board becomes a clue again
+                app.givens = givens_mask(&app.grid);
+                app.phase = Phase::EditClues;
+                app.message = Some("Editing clues".into());
+            }
+        },
+        Action::FreezeGivens => {
+            app.givens = givens_mask(&app.grid);
+            app.phase = Phase::Play;
+            let count = app.givens.iter().flatten().filter(|&&g| g).count();
+            app.message = Some(format!("Froze {} filled cells as givens", count));
+        }
+        Action::RotateClockwise => {
+            let (grid, givens) = (rotate_cw(&app.grid), rotate_cw(&app.givens));
+            app.transform("Rotated 90° clockwise", grid, givens);
+        }
+        Action::RotateCounterClockwise => {
+            let (grid, givens) = (rotate_ccw(&app.grid), rotate_ccw(&app.givens));
+            app.transform("Rotated 90° counter-clockwise", grid, givens);
+        }
+        Action::FlipHorizontal => {
+            let (grid, givens) = (flip_horizontal(&app.grid), flip_horizontal(&app.givens));
+            app.transform("Flipped left to right", grid, givens);
+        }
+        Action::FlipVertical => {
+            let (grid, givens) = (flip_vertical(&app.grid), flip_vertical(&app.givens));
+            app.transform("Flipped top to bottom", grid, givens);
+        }
+        Action::CheckSolution => match first_error(&app.grid) {
+            None => app.message = Some("Solved!".into()),
+            Some((row, col, kind)) => {
+                app.cursor = (row, col);
+                let problem = match kind {
+                    ErrorKind::Empty => "is empty",
+                    ErrorKind::Duplicate => "repeats a digit in its row, column or box",
+                };
+                app.message = Some(format!("Not solved: R{}C{} {}", row + 1, col + 1, problem));
+            }
+        },
+        Action::ResetToGivens => {
+            app.push_undo();
+            app.set_grid(givens_only(app));
+            app.message = Some("Reset to givens".into());
+            app.last_solve_time = None;
+        }
+        Action::Undo => match app.undo.pop() {
+            Some(snapshot) => {
+                app.conflicts.clear();
+                app.ambiguous.clear();
+                app.set_grid(snapshot.grid);
+                app.givens = snapshot.givens;
+                app.message = Some("Undid last change".into());
+            }
+            None => app.message = Some("Nothing to undo".into()),
+        },
+        Action::ToggleRulers => {
+            app.show_rulers = !app.show_rulers;
+        }
+        Action::ToggleInnerLines => {
+            app.inner_lines = !app.inner_lines;
+        }
+        Action::ToggleAutoAdvance => {
+            app.auto_advance = !app.auto_advance;
+            app.message = Some(
+                if app.auto_advance {
+                    "Auto-advance on"
+                } else {
+                    "Auto-advance off"
+                }
+                .into(),
+            );
+        }
+        Action::TogglePeers => {
+            app.shade_peers = !app.shade_peers;
+        }
+        Action::HighlightDigit => {
+            app.picking_highlight = true;
+            app.message = Some("Highlight which digit? 1-9, 0/Esc clears".into());
+        }
+        Action::ToggleBorders => {
+            app.borders = app.borders.toggled();
+            app.message = Some(match app.borders {
+                BorderStyle::Ascii => "ASCII borders".into(),
+                BorderStyle::Unicode => "Unicode borders".into(),
+            });
+        }
+        Action::ClearGrid if app.selection.is_some() => clear_selection(app),
+        Action::ClearGrid => {
+            app.push_undo();
+            app.set_grid([[0; 9]; 9]);
+            app.givens = [[false; 9]; 9];
+            app.phase = Phase::EditClues;
             app.message = Some("Cleared grid".into());
             app.last_solve_time = None;
         }
-        _ => match app.focus {
-            Focus::Grid => handle_grid_keys(app, key),
-            Focus::Solver => handle_solver_keys(app, key),
+    }
+    false
+}
+
+/// Backtracking search budget for an interactive solve, a few seconds at worst
+const SOLVE_NODE_LIMIT: usize = 20_000_000;
+
+/// The grid with only the givens filled in
+fn givens_only(app: &App) -> [[usize; 9]; 9] {
+    let mut clues = app.grid;
+    for (cell, given) in clues.iter_mut().flatten().zip(app.givens.iter().flatten()) {
+        if !given {
+            *cell = 0;
+        }
+    }
+    clues
+}
+
+/// Redraw interval while a solve runs, unless `SUDOKU_TICK_MS` sets another
+const DEFAULT_TICK: Duration = Duration::from_millis(50);
+
+/// Redraw interval while nothing animates; input still wakes the loop at once
+const IDLE_TICK: Duration = Duration::from_secs(1);
+
+/// Spinner frames, one per 100 ms of a running solve
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// A solve running on its own thread, so the UI keeps drawing meanwhile
+struct PendingSolve {
+    clues: [[usize; 9]; 9],
+    started: Instant,
+    /// After a failed `ContinueSolving`, say whether the givens alone can
+    /// still be solved
+    check_givens: bool,
+    outcome: mpsc::Receiver<SolveOutcome>,
+}
+
+/// What a background solve hands back to the UI thread
+struct SolveOutcome {
+    result: Result<[[usize; 9]; 9], SolveError>,
+    elapsed: Duration,
+    /// SAT timings or the uniqueness check, for the status bar
+    note: String,
+    /// Cells where two solutions differ, if the uniqueness check found two
+    ambiguous: Vec<(usize, usize)>,
+    /// SAT model problem behind the solution, if any
+    model_error: Option<String>,
+}
+
+/// Start solving `clues` with the selected solver in the background; the
+/// result replaces the grid when `poll_solve` picks it up
+fn solve_from(app: &mut App, clues: &[[usize; 9]; 9], check_givens: bool) {
+    let kind = current_solver_kind(app.solver_idx);
+    let clues = *clues;
+    let (send, outcome) = mpsc::channel();
+    std::thread::spawn(move || {
+        // The UI may have quit by the time this lands
+        let _ = send.send(run_solve(kind, &clues));
+    });
+    app.solving = Some(PendingSolve {
+        clues,
+        started: Instant::now(),
+        check_givens,
+        outcome,
+    });
+    app.message = Some("Solving...".into());
+}
+
+/// The solve itself and its status notes, off the UI thread
+fn run_solve(kind: SolverKind, clues: &[[usize; 9]; 9]) -> SolveOutcome {
+    let mut solver = make_solver(kind)
+        .with_max_nodes(SOLVE_NODE_LIMIT)
+        .with_timings();
+    let start = Instant::now();
+    let result = solve_caught(|| solver.solve(clues));
+    let elapsed = start.elapsed();
+    let mut ambiguous = Vec::new();
+    let note = match solver.sat_times() {
+        _ if result.is_err() => String::new(),
+        Some(times) => format!(
+            " (encode {:.1?}, solve {:.1?})",
+            times.encode_time, times.solve_time
+        ),
+        None if kind == SolverKind::Backtracking => {
+            let (note, cells) = uniqueness_note(clues);
+            ambiguous = cells;
+            note.to_string()
+        }
+        None => String::new(),
+    };
+    SolveOutcome {
+        result,
+        elapsed,
+        note,
+        ambiguous,
+        model_error: solver.sat_model_error().map(|e| e.to_string()),
+    }
+}
+
+/// Show the background solve's result once it has landed
+fn poll_solve(app: &mut App) {
+    let Some(pending) = &app.solving else {
+        return;
+    };
+    let outcome = match pending.outcome.try_recv() {
+        Ok(outcome) => outcome,
+        Err(mpsc::TryRecvError::Empty) => return,
+        Err(mpsc::TryRecvError::Disconnected) => {
+            app.solving = None;
+            app.message = Some("Solver error: the solver thread stopped".into());
+            return;
+        }
+    };
+    let Some(pending) = app.solving.take() else {
+        return;
+    };
+    let clues = pending.clues;
+    if !finish_solve(app, &clues, outcome)
+        && pending.check_givens
+        && app.phase == Phase::Play
+        && clues != givens_only(app)
+    {
+        let mut solver =
+            make_solver(current_solver_kind(app.solver_idx)).with_max_nodes(SOLVE_NODE_LIMIT);
+        if solver.is_satisfiable(&givens_only(app)) == Ok(true) {
+            app.message =
+                Some("Your entries rule out every solution; the puzzle itself is solvable".into());
+        }
+    }
+}
+
+/// Show a finished solve of `clues` in place of the grid. On failure the grid
+/// is left untouched; returns whether it solved.
+fn finish_solve(app: &mut App, clues: &[[usize; 9]; 9], outcome: SolveOutcome) -> bool {
+    let SolveOutcome {
+        result,
+        elapsed,
+        note,
+        ambiguous,
+        model_error,
+    } = outcome;
+    match result {
+        Ok(sol) => {
+            app.push_undo();
+            app.set_grid(sol);
+            app.ambiguous = ambiguous;
+            app.last_solve_time = Some(elapsed);
+            app.session_solve_time += elapsed;
+            app.session_solves += 1;
+            app.message = Some(format!("Solved in {} ms{}", elapsed.as_millis(), note));
+            if let Some(err) = model_error {
+                app.message = Some(format!("Solved, but the SAT {}", err));
+            }
+            true
+        }
+        Err(SolveError::Unsatisfiable) => {
+            app.last_solve_time = Some(elapsed);
+            app.session_solve_time += elapsed;
+            app.session_solves += 1;
+            let reason = explain_unsolvable(app, clues);
+            app.message = Some(format!(
+                "No solution ({} ms){}",
+                elapsed.as_millis(),
+                reason
+            ));
+            false
+        }
+        Err(SolveError::InvalidDigit { row, col, digit }) => {
+            app.last_solve_time = None;
+            app.cursor = (row, col);
+            app.message = Some(format!(
+                "Clue at R{}C{} is invalid ({})",
+                row + 1,
+                col + 1,
+                digit
+            ));
+            false
+        }
+        Err(SolveError::NodeLimitExceeded { limit }) => {
+            app.last_solve_time = None;
+            app.message = Some(format!(
+                "Gave up after {} nodes; SAT or Auto may finish",
+                limit
+            ));
+            false
+        }
+        Err(e) => {
+            app.last_solve_time = None;
+            app.message = Some(format!("Solver error: {}", e));
+            false
+        }
+    }
+}
+
+/// One solver's result in a race and how long after the start it came in
+type RaceEntry = (SolverKind, Result<[[usize; 9]; 9], SolveError>, Duration);
+
+/// How long each racer gets before it is stopped and marked DNF. SAT and auto
+/// stop on this; backtracking and exact cover stop at `SOLVE_NODE_LIMIT`,
+/// which takes about as long.
+const RACE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether the finishers' results can't all be right. Different grids are
+/// fine, since a puzzle with several solutions lets each solver pick its own;
+/// a grid that isn't a solution of `clues`, or one solver finding a solution
+/// while another proves there is none, is not.
+fn race_disagrees(
+    clues: &[[usize; 9]; 9],
+    outcomes: &[&Result<[[usize; 9]; 9], SolveError>],
+) -> bool {
+    let solved = outcomes.iter().any(|r| r.is_ok());
+    let unsolvable = outcomes.iter().any(|r| r.is_err());
+    let wrong = outcomes
+        .iter()
+        .any(|r| matches!(r, Ok(grid) if !is_solved(grid) || !respects_givens(clues, grid)));
+    (solved && unsolvable) || wrong
+}
+
+/// Run every solver on `clues` at once, each on its own thread, and show who
+/// finished first. Proving there is no solution counts as finishing; errors,
+/// panics, the node limit and `RACE_TIMEOUT` are DNF. The winner's solution
+/// is loaded.
+fn race(app: &mut App, clues: &[[usize; 9]; 9]) {
+    let start = Instant::now();
+    let mut results: Vec<RaceEntry> = std::thread::scope(|s| {
+        let handles: Vec<_> = SolverKind::ALL
+            .map(|kind| {
+                s.spawn(move || {
+                    let mut solver = make_solver(kind)
+                        .with_max_nodes(SOLVE_NODE_LIMIT)
+                        .with_timeout(RACE_TIMEOUT);
+                    let result = solve_caught(|| solver.solve(clues));
+                    (kind, result, start.elapsed())
+                })
+            })
+            .into();
+        SolverKind::ALL
+            .into_iter()
+            .zip(handles)
+            .map(|(kind, handle)| {
+                handle.join().unwrap_or_else(|_| {
+                    let err = SolveError::SolverBackendError("panicked".into());
+                    (kind, Err(err), start.elapsed())
+                })
+            })
+            .collect()
+    });
+    let finished = |r: &Result<_, SolveError>| matches!(r, Ok(_) | Err(SolveError::Unsatisfiable));
+    results.sort_by_key(|(_, result, time)| (!finished(result), *time));
+
+    let mut board = Vec::new();
+    for (place, (kind, result, time)) in results.iter().enumerate() {
+        if finished(result) {
+            board.push(format!("{}. {:?} {} ms", place + 1, kind, time.as_millis()));
+        } else {
+            board.push(format!("{:?} DNF", kind));
+        }
+    }
+    let mut message = format!("Race: {}", board.join(" · "));
+    let outcomes: Vec<_> = results
+        .iter()
+        .map(|(_, r, _)| r)
+        .filter(|r| finished(r))
+        .collect();
+    if race_disagrees(clues, &outcomes) {
+        message.push_str(" — solvers disagree!");
+    }
+    match outcomes.first() {
+        Some(Ok(solution)) => {
+            app.push_undo();
+            app.set_grid(*solution);
+            app.last_solve_time = Some(results[0].2);
+        }
+        Some(Err(_)) => message.push_str(" — no solution"),
+        None => {}
+    }
+    app.message = Some(message);
+}
+
+/// After a failed solve, mark the clues to blame in `app.conflicts` and move
+/// the cursor to the first: repeated digits if the clues break a rule outright,
+/// otherwise a minimal set of clues that can't all hold. Returns a status suffix.
+fn explain_unsolvable(app: &mut App, clues: &[[usize; 9]; 9]) -> String {
+    let repeats = conflicting_cells(clues);
+    let (cells, reason) = if !repeats.is_empty() {
+        (repeats, "red clues repeat a digit")
+    } else {
+        match explain_unsat(clues) {
+            Ok(Some(core)) => (core, "red clues can't all hold"),
+            _ => return String::new(),
+        }
+    };
+    app.cursor = cells[0];
+    app.conflicts = cells;
+    format!(": {}", reason)
+}
+
+/// Collection puzzles whose difficulty is remembered while paging
+const RATING_CACHE_SIZE: usize = 1024;
+
+/// How long the post-solve uniqueness check may run before giving up
+const UNIQUENESS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Status suffix saying whether the puzzle has a single solution and, if it
+/// has more, the cells where two of them differ
+fn uniqueness_note(puzzle: &[[usize; 9]; 9]) -> (&'static str, Vec<(usize, usize)>) {
+    match count_solutions_until(puzzle, 2, Some(Instant::now() + UNIQUENESS_TIMEOUT)) {
+        Some(1) => (" (unique)", Vec::new()),
+        // Same search order as the count, so this finds the pair just as fast
+        Some(_) => match find_two_solutions(puzzle) {
+            TwoSolutions::Two(first, second) => {
+                let cells = diff(&first, &second)
+                    .into_iter()
+                    .map(|(row, col, _, _)| (row, col))
+                    .collect();
+                (" (multiple solutions: the marked cells can differ)", cells)
+            }
+            _ => (" (multiple solutions exist)", Vec::new()),
         },
+        None => (" (uniqueness unknown: check timed out)", Vec::new()),
     }
-    Ok(false)
 }
 
 fn handle_solver_keys(app: &mut App, key: KeyEvent) {
@@ -193,13 +1234,113 @@ fn handle_solver_keys(app: &mut App, key: KeyEvent) {
         KeyCode::Char('0') => app.solver_idx = 0,
         KeyCode::Char('1') => app.solver_idx = 1.min(solver_titles().len() - 1),
         KeyCode::Char('2') => app.solver_idx = 2.min(solver_titles().len() - 1),
+        KeyCode::Char('3') => app.solver_idx = 3.min(solver_titles().len() - 1),
         KeyCode::Enter => app.focus = Focus::Grid,
         _ => {}
     }
 }
 
+/// Enter `d` (0 clears) at a cell, honouring the current phase; returns
+/// false if the cell can't be edited
+fn set_cell(app: &mut App, row: usize, col: usize, d: usize) -> bool {
+    if app.phase == Phase::Play && app.givens[row][col] {
+        app.message = Some(format!("R{}C{} is a given", row + 1, col + 1));
+        return false;
+    }
+    app.push_undo();
+    app.enter_digit(row, col, d);
+    check_dead_end(app);
+    true
+}
+
+/// Whether `(row, col)` lies in the Shift+Arrows selection
+fn in_selection(app: &App, row: usize, col: usize) -> bool {
+    app.selection.is_some_and(|((r0, c0), (r1, c1))| {
+        (r0.min(r1)..=r0.max(r1)).contains(&row) && (c0.min(c1)..=c0.max(c1)).contains(&col)
+    })
+}
+
+/// Empty every editable cell of the selection as one undo step and drop the
+/// selection
+fn clear_selection(app: &mut App) {
+    app.push_undo();
+    let mut cleared = 0;
+    for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+        if !in_selection(app, row, col) || (app.phase == Phase::Play && app.givens[row][col]) {
+            continue;
+        }
+        if app.grid[row][col] != 0 {
+            cleared += 1;
+        }
+        app.enter_digit(row, col, 0);
+    }
+    app.selection = None;
+    app.message = Some(format!("Cleared {} selected cells", cleared));
+}
+
+/// Step a cell to its next (or previous) legal digit, wrapping around; an empty
+/// cell starts at the first (or last) one
+fn cycle_candidate(app: &mut App, row: usize, col: usize, forward: bool) {
+    let mut others = app.grid;
+    others[row][col] = 0;
+    let digits = Candidates::from_grid(&others).digits(row, col);
+    let n = digits.len();
+    if n == 0 {
+        app.message = Some(format!("R{}C{} has no candidates", row + 1, col + 1));
+        return;
+    }
+    let next = match digits.iter().position(|&d| d == app.grid[row][col]) {
+        Some(i) if forward => digits[(i + 1) % n],
+        Some(i) => digits[(i + n - 1) % n],
+        None if forward => digits[0],
+        None => digits[n - 1],
+    };
+    set_cell(app, row, col, next);
+}
+
+/// The next cell after `(row, col)` in row-major order, wrapping from R9C9 to
+/// R1C1 and skipping givens while playing. Stays put if no other cell is editable.
+fn next_editable_cell(app: &App, row: usize, col: usize) -> (usize, usize) {
+    let start = row * 9 + col;
+    (1..81)
+        .map(|step| ((start + step) % 81 / 9, (start + step) % 9))
+        .find(|&(r, c)| app.phase == Phase::EditClues || !app.givens[r][c])
+        .unwrap_or((row, col))
+}
+
+/// Cursor movement and cell entry for the focused grid.
+///
+/// Digits go through `app.entry`, which switches mode by the grid's largest
+/// value: on 9x9 every digit is a whole value and takes effect immediately,
+/// while values up to 16 wait for a second digit, Enter, Esc or the entry
+/// timeout after a leading `1`, shown in the status bar meanwhile. Any other
+/// key drops a pending value. Values are typed in decimal, and the grid shows
+/// them in decimal too (`cell_label`), so typing `1`, `2` shows `12`; puzzle
+/// files keep their one-character hex labels.
 fn handle_grid_keys(app: &mut App, key: KeyEvent) {
     let (mut r, mut c) = app.cursor;
+    let now = Instant::now();
+    let typed = match key.code {
+        KeyCode::Char(ch) if ch.is_ascii_digit() => {
+            Some(app.entry.push((ch as u8 - b'0') as usize, now))
+        }
+        KeyCode::Enter => app.entry.commit(now).map(Entry::Commit),
+        // Esc drops a pending value before it drops the selection
+        KeyCode::Esc if app.entry.cancel() => return,
+        _ => {
+            app.entry.cancel();
+            None
+        }
+    };
+    let arrow = matches!(
+        key.code,
+        KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right
+    );
+    // Shift+Arrows grow the selection from where it started; plain arrows drop it
+    let anchor = match app.selection {
+        Some((anchor, _)) => anchor,
+        None => app.cursor,
+    };
     match key.code {
         KeyCode::Up => {
             if r > 0 {
@@ -221,16 +1362,51 @@ fn handle_grid_keys(app: &mut App, key: KeyEvent) {
                 c += 1;
             }
         }
-        KeyCode::Char(ch) if ch.is_ascii_digit() => {
-            let d = (ch as u8 - b'0') as usize;
-            app.grid[r][c] = d;
-        }
+        _ if typed.is_some() => match typed {
+            Some(Entry::Commit(d)) => {
+                let edited = set_cell(app, r, c, d);
+                if edited && app.auto_advance {
+                    (r, c) = next_editable_cell(app, r, c);
+                }
+            }
+            Some(Entry::Rejected(value)) => {
+                app.message = Some(format!("{} is not a value on this grid", value));
+            }
+            _ => {}
+        },
         KeyCode::Backspace | KeyCode::Delete => {
-            app.grid[r][c] = 0;
+            set_cell(app, r, c, 0);
         }
+        KeyCode::Char(sign @ ('+' | '-')) => cycle_candidate(app, r, c, sign == '+'),
+        KeyCode::Esc => app.selection = None,
         _ => {}
     }
     app.cursor = (r, c);
+    if arrow {
+        app.selection = key
+            .modifiers
+            .contains(KeyModifiers::SHIFT)
+            .then_some((anchor, app.cursor));
+    }
+}
+
+/// Flag edits that break a rule or leave some cell with no possible digit
+fn check_dead_end(app: &mut App) {
+    if has_conflicts(&app.grid) {
+        app.message = Some("This entry breaks a row, column or box rule".into());
+    } else if let Some((r, c)) = find_dead_cell(&app.grid) {
+        app.message = Some(format!(
+            "This entry makes the puzzle unsolvable (R{}C{} has no candidates)",
+            r + 1,
+            c + 1
+        ));
+    } else if app
+        .message
+        .as_deref()
+        .is_some_and(|m| m.starts_with("This entry"))
+    {
+        app.message = None;
+    }
 }
 
 fn ui(f: &mut ratatui::Frame<'_>, app: &App) {
@@ -248,48 +1424,94 @@ fn ui(f: &mut ratatui::Frame<'_>, app: &App) {
     let tabs = Tabs::new(titles)
         .select(app.solver_idx)
         .block(Block::default().title("Solver").borders(Borders::ALL))
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        );
+        .highlight_style(app.colors.accent().add_modifier(Modifier::BOLD));
     f.render_widget(tabs, chunks[0]);
 
-    // Grid drawing; include last solve time in the title if available
-    let grid_title = if let Some(t) = app.last_solve_time {
-        format!("Sudoku  —  Last: {} ms", t.as_millis())
-    } else {
-        "Sudoku".to_string()
-    };
+    // Grid drawing; include the collection position and last solve time in the title
+    let mut grid_title = "Sudoku".to_string();
+    if !app.puzzles.is_empty() {
+        grid_title += &format!("  —  Puzzle {}/{}", app.puzzle_idx + 1, app.puzzles.len());
+        if let Some(name) = &app.puzzles[app.puzzle_idx].name {
+            grid_title += &format!(": {}", name);
+        }
+        if let Some(first) = app.duplicate_of[app.puzzle_idx] {
+            grid_title += &format!(" (duplicate of #{})", first + 1);
+        }
+        if let Some(rating) = app.rating {
+            grid_title += &format!(" [{}]", rating);
+        }
+    }
+    if let Some(t) = app.last_solve_time {
+        grid_title += &format!("  —  Last: {} ms", t.as_millis());
+    }
     let grid_block = Block::default().title(grid_title).borders(Borders::ALL);
-    let lines = render_grid_lines(&app.grid, app.cursor);
+    let lines = render_grid_lines(app);
     let para = Paragraph::new(lines).block(grid_block);
     f.render_widget(para, chunks[1]);
 
     // Status/help section with right-aligned time indicator
     let status_outer = Block::default()
         .borders(Borders::ALL)
-        .title(match app.focus {
-            Focus::Grid => "Focus: Grid",
-            Focus::Solver => "Focus: Solver",
+        .title(match (app.focus, app.phase) {
+            (Focus::Grid, Phase::EditClues) => "Focus: Grid  —  Editing clues",
+            (Focus::Grid, Phase::Play) => "Focus: Grid  —  Playing",
+            (Focus::Solver, Phase::EditClues) => "Focus: Solver  —  Editing clues",
+            (Focus::Solver, Phase::Play) => "Focus: Solver  —  Playing",
         });
     f.render_widget(status_outer.clone(), chunks[2]);
     let inner = status_outer.inner(chunks[2]);
     let status_chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(inner);
 
-    let left_status = app.message.clone().unwrap_or_else(|| {
-        "Tab: focus • Arrows/0-9: edit • s: solve • d: default • c: clear • q: quit • ?: help"
+    let spinning = app.solving.as_ref().map(|pending| {
+        let elapsed = pending.started.elapsed();
+        let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+        format!("Solving {} {:.1} s", frame, elapsed.as_secs_f64())
+    });
+    let left_status = spinning.or_else(|| app.message.clone()).unwrap_or_else(|| {
+        "Tab: focus • Arrows/0-9: edit • s: solve • d: default • c: clear • :: commands • q: quit • ?: help"
             .to_string()
     });
     let left_para = Paragraph::new(Line::from(left_status));
     f.render_widget(left_para, status_chunks[0]);
 
+    // Candidates of the focused cell, how often the highlighted digit is
+    // placed, the share of conflict-free units, then the last solve time
+    let (r, c) = app.cursor;
+    let mut right_status = Vec::new();
+    if let Some(value) = app.entry.pending(Instant::now()) {
+        right_status.push(format!("entry: {}_ (Enter sets, Esc cancels)", value));
+    }
+    if app.grid[r][c] == 0 {
+        let digits = app.pencil_marks().digits(r, c);
+        let list: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+        right_status.push(format!("candidates: {}", list.join(" ")));
+    } else if app.givens[r][c] {
+        right_status.push("given".to_string());
+    }
+    if let Some(d) = app.highlight_digit {
+        right_status.push(format!("{}s placed: {}/9", d, digit_counts(&app.grid)[d]));
+    }
+    let valid = satisfaction(&app.grid);
+    if valid < 1.0 {
+        right_status.push(format!("units valid: {:.0}%", valid * 100.0));
+    }
     if let Some(t) = app.last_solve_time {
-        let right_para = Paragraph::new(Line::from(format!("Last solve: {} ms", t.as_millis())))
-            .alignment(Alignment::Right);
+        right_status.push(format!("Last solve: {} ms", t.as_millis()));
+    }
+    if app.session_solves > 0 {
+        right_status.push(format!(
+            "session: {} ms total, {} solve{}",
+            app.session_solve_time.as_millis(),
+            app.session_solves,
+            if app.session_solves == 1 { "" } else { "s" }
+        ));
+    }
+    if !right_status.is_empty() {
+        let right_para =
+            Paragraph::new(Line::from(right_status.join("  •  "))).alignment(Alignment::Right);
         f.render_widget(right_para, status_chunks[1]);
     }
 
@@ -299,24 +1521,48 @@ fn ui(f: &mut ratatui::Frame<'_>, app: &App) {
         let help_lines = vec![
             Line::from(Span::styled("Controls", Style::default().add_modifier(Modifier::BOLD))),
             Line::from(""),
-            Line::from(Span::styled("Global", Style::default().fg(Color::Yellow))),
-            Line::from("  q: quit    ?,h: toggle help"),
+            Line::from(Span::styled("Global", app.colors.accent())),
+            Line::from("  q: quit    ?,h: toggle help    :: command palette"),
             Line::from(""),
-            Line::from(Span::styled("Focus", Style::default().fg(Color::Yellow))),
+            Line::from(Span::styled("Focus", app.colors.accent())),
             Line::from("  Tab: switch focus between Grid and Solver tabs"),
             Line::from(""),
-            Line::from(Span::styled("Grid editing", Style::default().fg(Color::Yellow))),
+            Line::from(Span::styled("Grid editing", app.colors.accent())),
             Line::from("  Arrows: move cursor    0-9: set cell (0 clears)"),
+            Line::from("  Shift+Arrows: select a rectangle (Esc or a plain arrow drops it)"),
             Line::from("  Backspace/Delete: clear current cell"),
-            Line::from("  c: clear entire grid    s: solve with selected solver"),
-            Line::from("  d: load sample default puzzle"),
+            Line::from("  +/-: cycle the current cell through its legal digits"),
+            Line::from("  a: toggle moving to the next cell (skipping clues) after typing a digit"),
+            Line::from("  e: lock the filled cells as clues and play, or go back to editing clues"),
+            Line::from("     (while playing, clues are shown bold and can't be changed)"),
+            Line::from("  g: freeze all filled cells, your entries too, as the puzzle's givens"),
+            Line::from("  c: clear grid (or selection)    s: solve the clues with selected solver"),
+            Line::from("     (if there is no solution, the clues to blame turn red)"),
+            Line::from("     (if there are several, cells that differ between two are marked)"),
+            Line::from("  R: reset to the givens, clearing every other cell"),
+            Line::from("  S: solve the rest, keeping your entries as extra clues"),
+            Line::from("  x: race every solver on the clues and load the winner's solution"),
+            Line::from("  n: apply the next logical step (single, or X-Wing elimination)"),
+            Line::from("  i: hint: point at the next logical step and explain it"),
+            Line::from("  o: fill every cell a single round of singles forces (undo reverts all)"),
+            Line::from("  d: load sample default puzzle    b: toggle ASCII/Unicode borders"),
+            Line::from("  D: load the next sample puzzle, cycling easy, medium, hard, expert"),
+            Line::from("  [/]: rotate 90° counter-clockwise/clockwise    m/v: flip left-right/top-bottom"),
+            Line::from("  u: undo the last change    k: check the grid, jumping to the first problem"),
+            Line::from("  r: toggle row/column coordinate rulers    l: toggle lines between cells"),
+            Line::from("  p: toggle shading of the cursor's row, column and box"),
+            Line::from("  PageUp/PageDown: previous/next puzzle of a --file collection"),
+            Line::from("  f then 1-9: highlight a digit and where it can go (0/Esc clears)"),
             Line::from(""),
-            Line::from(Span::styled("Solver selection", Style::default().fg(Color::Yellow))),
+            Line::from(Span::styled("Solver selection", app.colors.accent())),
             Line::from("  Left/Right: change solver tab"),
-            Line::from("  0/1/2: jump to specific solver    Enter: back to Grid"),
+            Line::from("  0-3: jump to specific solver    Enter: back to Grid"),
             Line::from(""),
-            Line::from("SAT and Backtracking are implemented; ExactCover coming soon."),
+            Line::from("SAT, Backtracking, Auto (logic, then backtracking, then SAT) and"),
+            Line::from("ExactCover (Algorithm X with dancing links) are implemented."),
             Line::from("The last solve time is shown in the Sudoku title and the status bar."),
+            Line::from("The status bar also lists the candidates of the focused empty cell."),
+            Line::from("With conflicts, it also shows the share of units still valid."),
             Line::from("Press Esc, ? or h to close this help."),
         ];
         let help =
@@ -324,6 +1570,10 @@ fn ui(f: &mut ratatui::Frame<'_>, app: &App) {
         f.render_widget(Clear, area); // clear area beneath overlay
         f.render_widget(help, area);
     }
+
+    if let Some(palette) = &app.palette {
+        palette.render(f, centered_rect(60, 60, f.size()), app.colors);
+    }
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -346,37 +1596,334 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     horiz[1]
 }
 
-fn render_grid_lines(grid: &[[usize; 9]; 9], cursor: (usize, usize)) -> Vec<Line<'static>> {
-    let mut lines = Vec::with_capacity(13);
-    for r in 0..9 {
-        if r > 0 && r % 3 == 0 {
-            lines.push(Line::from("------+-------+------"));
+/// Whether two distinct cells share a row, column or box
+fn is_peer(a: (usize, usize), b: (usize, usize)) -> bool {
+    a != b && (a.0 == b.0 || a.1 == b.1 || box_index(a.0, a.1) == box_index(b.0, b.1))
+}
+
+fn render_grid_lines(app: &App) -> Vec<Line<'static>> {
+    let grid = &app.grid;
+    let colors = app.colors;
+    let rulers = app.show_rulers.then(|| colors.ruler());
+    let marks = app.pencil_marks();
+    layout_grid(3, app.borders, app.inner_lines, rulers, |r, c| {
+        let mut style = Style::default();
+        if app.givens[r][c] {
+            style = style.add_modifier(Modifier::BOLD);
         }
-        let mut spans: Vec<Span> = Vec::with_capacity(20);
-        for c in 0..9 {
-            if c > 0 {
-                if c % 3 == 0 {
-                    spans.push(Span::raw("| "));
-                } else {
-                    spans.push(Span::raw(""));
-                }
+        if app.conflicts.contains(&(r, c)) {
+            style = style.patch(colors.conflict());
+        }
+        if app.ambiguous.contains(&(r, c)) {
+            style = style.patch(colors.ambiguous());
+        }
+        if app.shade_peers && is_peer(app.cursor, (r, c)) {
+            style = style.patch(colors.peer());
+        }
+        if in_selection(app, r, c) {
+            style = style.patch(colors.selection());
+        }
+        if let Some(d) = app.highlight_digit {
+            if grid[r][c] == d {
+                style = style.patch(colors.highlight());
+            } else if grid[r][c] == 0 && marks.contains(r, c, d) {
+                style = colors.candidate_spot();
             }
-            let val = grid[r][c];
-            let ch = if val == 0 {
-                '.'
-            } else {
-                char::from(b'0' + val as u8)
-            };
-            let mut span = Span::raw(format!("{} ", ch));
-            if (r, c) == cursor {
-                span.style = Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD);
+        }
+        if (r, c) == app.cursor {
+            style = colors.cursor();
+        }
+        (grid[r][c], style)
+    })
+}
+
+/// Label for a cell value in the TUI, `.` for empty. Values are decimal at
+/// every size, the way they are typed (see `handle_grid_keys`), right-aligned
+/// to `width` so 16x16 cells line up.
+fn cell_label(value: usize, width: usize) -> String {
+    if value == 0 {
+        format!("{:>width$}", '.')
+    } else {
+        format!("{:>width$}", value)
+    }
+}
+
+/// Lay out a grid with `order²` rows and columns as text, one `Line` per cell
+/// row plus box separators (and, with `inner_lines`, light lines between
+/// cells) and, with a ruler style, coordinate rulers.
+///
+/// `cell(row, col)` gives each cell's value and style. Every width follows
+/// from `order`: cells hold a `cell_label` as wide as the largest value and a
+/// space, and all rows and separators come out the same width.
+fn layout_grid(
+    order: usize,
+    borders: BorderStyle,
+    inner_lines: bool,
+    rulers: Option<Style>,
+    cell: impl Fn(usize, usize) -> (usize, Style),
+) -> Vec<Line<'static>> {
+    let n = order * order;
+    let label_width = n.to_string().len();
+    let cell_width = label_width + 1;
+    let gutter = if rulers.is_some() {
+        " ".repeat(cell_width)
+    } else {
+        String::new()
+    };
+    let mut lines = Vec::with_capacity(n + order + 1);
+    if let Some(ruler) = rulers {
+        let mut header = gutter.clone();
+        for c in 0..n {
+            if line_before(order, c, inner_lines).is_some() {
+                header.push_str("  ");
             }
-            spans.push(span);
+            header.push_str(&cell_label(c + 1, label_width));
+            header.push(' ');
+        }
+        lines.push(Line::from(Span::styled(header, ruler)));
+    }
+    for r in 0..n {
+        if let Some(heavy) = line_before(order, r, inner_lines) {
+            let separator = borders.separator_row(order, cell_width, heavy, inner_lines);
+            lines.push(Line::from(format!("{}{}", gutter, separator)));
+        }
+        let mut spans: Vec<Span> = Vec::with_capacity(2 * n + 1);
+        if let Some(ruler) = rulers {
+            let label = cell_label(r + 1, label_width);
+            spans.push(Span::styled(format!("{} ", label), ruler));
+        }
+        for c in 0..n {
+            if let Some(heavy) = line_before(order, c, inner_lines) {
+                spans.push(Span::raw(borders.column_separator(heavy)));
+            }
+            let (value, style) = cell(r, c);
+            let label = cell_label(value, label_width);
+            spans.push(Span::styled(format!("{} ", label), style));
         }
         lines.push(Line::from(spans));
     }
     lines
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    /// Writer whose bytes stay readable after its owner is dropped
+    #[derive(Clone, Default)]
+    struct SharedBuf(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn terminal_guard_restores_the_screen_when_a_panic_unwinds() {
+        let buf = SharedBuf::default();
+        let out = buf.clone();
+        let result = panic::catch_unwind(AssertUnwindSafe(move || {
+            let _guard = TerminalGuard { out, raw: false };
+            panic!("drawing failed");
+        }));
+        assert!(result.is_err());
+        let mut expected = Vec::new();
+        expected.execute(LeaveAlternateScreen).unwrap();
+        expected.execute(Show).unwrap();
+        assert_eq!(*buf.0.borrow(), expected);
+    }
+
+    #[test]
+    fn racers_disagree_only_on_wrong_or_contradictory_answers() {
+        let empty = [[0; 9]; 9];
+        let first = make_solver(SolverKind::Backtracking).solve(&empty).unwrap();
+        let second = rotate_cw(&first);
+        assert_ne!(first, second);
+        // Two different solutions of an open puzzle are both right
+        assert!(!race_disagrees(&empty, &[&Ok(first), &Ok(second)]));
+        assert!(!race_disagrees(&empty, &[&Err(SolveError::Unsatisfiable)]));
+        assert!(race_disagrees(
+            &empty,
+            &[&Ok(first), &Err(SolveError::Unsatisfiable)]
+        ));
+        // A grid that breaks a rule or drops a given is wrong
+        let mut broken = first;
+        broken[0].swap(0, 1);
+        assert!(race_disagrees(&empty, &[&Ok(first), &Ok(broken)]));
+        let mut clues = empty;
+        clues[0][0] = first[0][0] % 9 + 1;
+        assert!(race_disagrees(&clues, &[&Ok(first)]));
+    }
+
+    #[test]
+    fn solve_caught_reports_a_panic_and_clears_the_quiet_flag() {
+        let result = solve_caught(|| panic!("backend exploded"));
+        assert_eq!(
+            result,
+            Err(SolveError::SolverBackendError(
+                "panicked: backend exploded".into()
+            ))
+        );
+        assert!(!CATCHING.get());
+        assert_eq!(solve_caught(|| Ok([[1; 9]; 9])), Ok([[1; 9]; 9]));
+    }
+
+    #[test]
+    fn layout_grid_renders_16x16_values_in_decimal() {
+        let grid: Vec<Vec<usize>> = (0..16)
+            .map(|r| {
+                (0..16)
+                    .map(|c| (4 * (r % 4) + r / 4 + c) % 16 + 1)
+                    .collect()
+            })
+            .collect();
+        let lines = layout_grid(4, BorderStyle::Ascii, false, None, |r, c| {
+            (grid[r][c], Style::default())
+        });
+        // 16 cell rows and a separator between each of the 4 bands
+        assert_eq!(lines.len(), 19);
+        assert_eq!(
+            text(&lines[0]).trim_end(),
+            " 1  2  3  4 |  5  6  7  8 |  9 10 11 12 | 13 14 15 16"
+        );
+        assert!(text(&lines[4]).starts_with("------------+-"));
+        let cells: Vec<usize> = lines
+            .iter()
+            .filter(|line| !text(line).starts_with('-'))
+            .flat_map(|line| {
+                text(line)
+                    .split_whitespace()
+                    .filter(|label| *label != "|")
+                    .map(|label| label.parse().unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(cells, grid.concat());
+    }
+
+    /// Draw `lines` on a test terminal and return each screen row, trailing
+    /// blanks trimmed, down to the last row with anything on it
+    fn rendered_rows(lines: Vec<Line<'static>>) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+        terminal
+            .draw(|f| f.render_widget(Paragraph::new(lines), f.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let mut rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                let row: String = (0..buffer.area.width)
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect();
+        while rows.last().is_some_and(|row| row.is_empty()) {
+            rows.pop();
+        }
+        rows
+    }
+
+    #[test]
+    fn every_rendered_row_is_as_wide_as_the_grid() {
+        for order in 2..=4 {
+            let n = order * order;
+            for borders in [BorderStyle::Ascii, BorderStyle::Unicode] {
+                for inner_lines in [false, true] {
+                    for rulers in [None, Some(Style::default())] {
+                        let lines = layout_grid(order, borders, inner_lines, rulers, |r, c| {
+                            ((r * order + r / order + c) % n + 1, Style::default())
+                        });
+                        let rows = rendered_rows(lines);
+                        let separators = if inner_lines { n - 1 } else { order - 1 };
+                        let header = usize::from(rulers.is_some());
+                        assert_eq!(rows.len(), n + separators + header, "order {}", order);
+                        // n cells of a label and a space, with a 2-wide
+                        // bar before each separated column, less the
+                        // trailing space
+                        let cell_width = n.to_string().len() + 1;
+                        let gutter = cell_width * header;
+                        let width = gutter + cell_width * n + 2 * separators - 1;
+                        for row in &rows {
+                            assert_eq!(row.chars().count(), width, "order {}: {:?}", order, row);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inner_lines_are_light_and_boxes_heavy() {
+        let lines = layout_grid(3, BorderStyle::Unicode, true, None, |r, c| {
+            ((r * 3 + r / 3 + c) % 9 + 1, Style::default())
+        });
+        // 9 cell rows, 2 heavy and 6 light separators
+        assert_eq!(lines.len(), 17);
+        assert_eq!(
+            text(&lines[0]).trim_end(),
+            "1 │ 2 │ 3 ┃ 4 │ 5 │ 6 ┃ 7 │ 8 │ 9"
+        );
+        assert_eq!(text(&lines[1]), "──┼───┼───╂───┼───┼───╂───┼───┼──");
+        assert_eq!(text(&lines[5]), "━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━");
+    }
+
+    #[test]
+    fn steps_while_editing_clues_add_clues() {
+        let mut app = App::default();
+        app.grid = default_puzzle();
+        app.givens = givens_mask(&app.grid);
+        app.cands = Candidates::from_grid(&app.grid);
+        run_action(&mut app, Action::Step);
+        run_action(&mut app, Action::FillObvious);
+        assert_eq!(app.phase, Phase::EditClues);
+        assert_eq!(app.givens, givens_mask(&app.grid));
+    }
+
+    #[test]
+    fn freeze_givens_locks_the_players_entries() {
+        let mut app = App::default();
+        app.load_puzzle(default_puzzle());
+        let (row, col) = next_editable_cell(&app, 0, 0);
+        let digit = Candidates::from_grid(&app.grid).digits(row, col)[0];
+        assert!(set_cell(&mut app, row, col, digit));
+        assert!(!app.givens[row][col]);
+        run_action(&mut app, Action::FreezeGivens);
+        assert!(app.givens[row][col]);
+        assert!(!set_cell(&mut app, row, col, 0));
+        assert_eq!(app.grid[row][col], digit);
+    }
+
+    #[test]
+    fn solves_run_in_the_background_and_hold_off_edits() {
+        let mut app = App::default();
+        app.load_puzzle(default_puzzle());
+        let clues = app.grid;
+        run_action(&mut app, Action::Solve);
+        assert!(app.solving.is_some());
+        assert_eq!(app.grid, clues);
+
+        let (row, col) = next_editable_cell(&app, 0, 0);
+        app.cursor = (row, col);
+        assert!(!handle_key(&mut app, KeyEvent::from(KeyCode::Char('5'))).unwrap());
+        assert_eq!(app.grid, clues);
+
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while app.solving.is_some() && Instant::now() < deadline {
+            std::thread::sleep(DEFAULT_TICK);
+            poll_solve(&mut app);
+        }
+        assert!(app.solving.is_none());
+        assert!(is_solved(&app.grid), "{:?}", app.message);
+        assert!(respects_givens(&clues, &app.grid));
+    }
+}
@@ -0,0 +1,315 @@
+use crossterm::event::KeyCode;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+
+use super::ColorSupport;
+
+/// Everything the user can trigger, independent of how it was triggered
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    Quit,
+    ToggleHelp,
+    OpenPalette,
+    ToggleFocus,
+    Solve,
+    LoadDefault,
+    LoadSample,
+    ClearGrid,
+    ToggleBorders,
+    Step,
+    Hint,
+    FillObvious,
+    HighlightDigit,
+    NextPuzzle,
+    PrevPuzzle,
+    TogglePhase,
+    FreezeGivens,
+    ToggleRulers,
+    ToggleInnerLines,
+    TogglePeers,
+    RotateClockwise,
+    RotateCounterClockwise,
+    FlipHorizontal,
+    FlipVertical,
+    Undo,
+    ContinueSolving,
+    Race,
+    CheckSolution,
+    ResetToGivens,
+    ToggleAutoAdvance,
+}
+
+pub struct Command {
+    pub name: &'static str,
+    pub keys: &'static str,
+    pub action: Action,
+}
+
+/// Dispatch table for global actions; also the source for the palette list
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "Solve",
+        keys: "s",
+        action: Action::Solve,
+    },
+    Command {
+        name: "Next logical step",
+        keys: "n",
+        action: Action::Step,
+    },
+    Command {
+        name: "Hint (explain the next step)",
+        keys: "i",
+        action: Action::Hint,
+    },
+    Command {
+        name: "Fill every obvious cell",
+        keys: "o",
+        action: Action::FillObvious,
+    },
+    Command {
+        name: "Load default puzzle",
+        keys: "d",
+        action: Action::LoadDefault,
+    },
+    Command {
+        name: "Load the next sample puzzle (easy to expert)",
+        keys: "D",
+        action: Action::LoadSample,
+    },
+    Command {
+        name: "Next puzzle",
+        keys: "PageDown",
+        action: Action::NextPuzzle,
+    },
+    Command {
+        name: "Previous puzzle",
+        keys: "PageUp",
+        action: Action::PrevPuzzle,
+    },
+    Command {
+        name: "Lock clues / edit clues",
+        keys: "e",
+        action: Action::TogglePhase,
+    },
+    Command {
+        name: "Freeze filled cells as givens",
+        keys: "g",
+        action: Action::FreezeGivens,
+    },
+    Command {
+        name: "Clear grid (or just the selected cells)",
+        keys: "c",
+        action: Action::ClearGrid,
+    },
+    Command {
+        name: "Highlight digit",
+        keys: "f",
+        action: Action::HighlightDigit,
+    },
+    Command {
+        name: "Toggle box borders",
+        keys: "b",
+        action: Action::ToggleBorders,
+    },
+    Command {
+        name: "Toggle coordinate rulers",
+        keys: "r",
+        action: Action::ToggleRulers,
+    },
+    Command {
+        name: "Toggle light lines between cells",
+        keys: "l",
+        action: Action::ToggleInnerLines,
+    },
+    Command {
+        name: "Toggle auto-advance after entering a digit",
+        keys: "a",
+        action: Action::ToggleAutoAdvance,
+    },
+    Command {
+        name: "Toggle row/column/box shading",
+        keys: "p",
+        action: Action::TogglePeers,
+    },
+    Command {
+        name: "Rotate clockwise",
+        keys: "]",
+        action: Action::RotateClockwise,
+    },
+    Command {
+        name: "Rotate counter-clockwise",
+        keys: "[",
+        action: Action::RotateCounterClockwise,
+    },
+    Command {
+        name: "Flip left to right",
+        keys: "m",
+        action: Action::FlipHorizontal,
+    },
+    Command {
+        name: "Flip top to bottom",
+        keys: "v",
+        action: Action::FlipVertical,
+    },
+    Command {
+        name: "Solve the rest, keeping my entries",
+        keys: "S",
+        action: Action::ContinueSolving,
+    },
+    Command {
+        name: "Race every solver",
+        keys: "x",
+        action: Action::Race,
+    },
+    Command {
+        name: "Check solution",
+        keys: "k",
+        action: Action::CheckSolution,
+    },
+    Command {
+        name: "Reset to givens",
+        keys: "R",
+        action: Action::ResetToGivens,
+    },
+    Command {
+        name: "Undo",
+        keys: "u",
+        action: Action::Undo,
+    },
+    Command {
+        name: "Switch focus",
+        keys: "Tab",
+        action: Action::ToggleFocus,
+    },
+    Command {
+        name: "Toggle help",
+        keys: "?, h",
+        action: Action::ToggleHelp,
+    },
+    Command {
+        name: "Command palette",
+        keys: ":",
+        action: Action::OpenPalette,
+    },
+    Command {
+        name: "Quit",
+        keys: "q",
+        action: Action::Quit,
+    },
+];
+
+/// Global keybindings; keys not listed here fall through to the focused pane
+pub fn action_for_key(code: KeyCode) -> Option<Action> {
+    match code {
+        KeyCode::Char('q') => Some(Action::Quit),
+        KeyCode::Char('?') | KeyCode::Char('h') => Some(Action::ToggleHelp),
+        KeyCode::Char(':') => Some(Action::OpenPalette),
+        KeyCode::Tab => Some(Action::ToggleFocus),
+        KeyCode::Char('s') => Some(Action::Solve),
+        KeyCode::Char('d') => Some(Action::LoadDefault),
+        KeyCode::Char('D') => Some(Action::LoadSample),
+        KeyCode::Char('c') => Some(Action::ClearGrid),
+        KeyCode::Char('b') => Some(Action::ToggleBorders),
+        KeyCode::Char('n') => Some(Action::Step),
+        KeyCode::Char('i') => Some(Action::Hint),
+        KeyCode::Char('o') => Some(Action::FillObvious),
+        KeyCode::Char('f') => Some(Action::HighlightDigit),
+        KeyCode::Char('r') => Some(Action::ToggleRulers),
+        KeyCode::Char('l') => Some(Action::ToggleInnerLines),
+        KeyCode::Char('p') => Some(Action::TogglePeers),
+        KeyCode::Char('a') => Some(Action::ToggleAutoAdvance),
+        KeyCode::Char(']') => Some(Action::RotateClockwise),
+        KeyCode::Char('[') => Some(Action::RotateCounterClockwise),
+        KeyCode::Char('m') => Some(Action::FlipHorizontal),
+        KeyCode::Char('v') => Some(Action::FlipVertical),
+        KeyCode::Char('u') => Some(Action::Undo),
+        KeyCode::Char('k') => Some(Action::CheckSolution),
+        KeyCode::Char('S') => Some(Action::ContinueSolving),
+        KeyCode::Char('x') => Some(Action::Race),
+        KeyCode::Char('R') => Some(Action::ResetToGivens),
+        KeyCode::Char('e') => Some(Action::TogglePhase),
+        KeyCode::Char('g') => Some(Action::FreezeGivens),
+        KeyCode::PageDown => Some(Action::NextPuzzle),
+        KeyCode::PageUp => Some(Action::PrevPuzzle),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+pub struct Palette {
+    query: String,
+    selected: usize,
+}
+
+pub enum PaletteOutcome {
+    Open,
+    Close,
+    Run(Action),
+}
+
+impl Palette {
+    /// Commands whose name contains the query (case-insensitive)
+    pub fn matches(&self) -> Vec<&'static Command> {
+        let query = self.query.to_lowercase();
+        COMMANDS
+            .iter()
+            .filter(|c| c.name.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> PaletteOutcome {
+        match code {
+            KeyCode::Esc => return PaletteOutcome::Close,
+            KeyCode::Enter => {
+                return match self.matches().get(self.selected) {
+                    Some(cmd) => PaletteOutcome::Run(cmd.action),
+                    None => PaletteOutcome::Close,
+                };
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.matches().len() => self.selected += 1,
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            KeyCode::Char(ch) => {
+                self.query.push(ch);
+                self.selected = 0;
+            }
+            _ => {}
+        }
+        PaletteOutcome::Open
+    }
+
+    pub fn render(&self, f: &mut ratatui::Frame<'_>, area: Rect, colors: ColorSupport) {
+        let block = Block::default().title("Commands").borders(Borders::ALL);
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+        if inner.height == 0 {
+            return;
+        }
+
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled(":", colors.accent()),
+            Span::raw(self.query.clone()),
+        ]));
+        f.render_widget(prompt, Rect { height: 1, ..inner });
+
+        let items: Vec<ListItem> = self
+            .matches()
+            .iter()
+            .map(|c| ListItem::new(format!("{:<24} {}", c.name, c.keys)))
+            .collect();
+        let list = List::new(items).highlight_style(colors.cursor());
+        let list_area = Rect {
+            y: inner.y + 1,
+            height: inner.height - 1,
+            ..inner
+        };
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        f.render_stateful_widget(list, list_area, &mut state);
+    }
+}
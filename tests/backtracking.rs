@@ -0,0 +1,112 @@
+use sudoku_sat::grid::{diff, has_conflicts, is_solved, respects_givens};
+use sudoku_sat::io::parse_line;
+use sudoku_sat::puzzles::{default_puzzle, sample_puzzle};
+use sudoku_sat::solver::SudokuSolver;
+use sudoku_sat::solver::auto::AUTO_BACKTRACK_NODES;
+use sudoku_sat::solver::backtracking::{
+    BacktrackingSudokuSolver, TwoSolutions, UniqueResult, count_solutions, count_solutions_until,
+    find_two_solutions, solve_all, unique_solution,
+};
+use sudoku_sat::solver::logic::Difficulty;
+
+/// Built to defeat row-major backtracking: the first row's digits are the
+/// last ones it would try
+const ANTI_BACKTRACKING: &str =
+    "..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9";
+
+#[test]
+fn solves_every_sample_within_the_auto_node_budget() {
+    for difficulty in Difficulty::ALL {
+        let puzzle = sample_puzzle(difficulty);
+        let solution = BacktrackingSudokuSolver::default()
+            .with_max_nodes(AUTO_BACKTRACK_NODES)
+            .solve(&puzzle)
+            .unwrap();
+        assert!(is_solved(&solution), "{}", difficulty);
+        assert!(respects_givens(&puzzle, &solution), "{}", difficulty);
+    }
+}
+
+#[test]
+fn most_constrained_cell_choice_handles_the_anti_backtracking_puzzle() {
+    let puzzle = parse_line(ANTI_BACKTRACKING).unwrap();
+    let solution = BacktrackingSudokuSolver::default()
+        .with_max_nodes(AUTO_BACKTRACK_NODES)
+        .solve(&puzzle)
+        .unwrap();
+    assert!(is_solved(&solution));
+    assert!(respects_givens(&puzzle, &solution));
+}
+
+#[test]
+fn unique_solution_reports_each_outcome() {
+    let puzzle = default_puzzle();
+    let solution = BacktrackingSudokuSolver::default().solve(&puzzle).unwrap();
+    assert_eq!(
+        unique_solution(&puzzle),
+        UniqueResult::Unique(Box::new(solution))
+    );
+
+    match unique_solution(&[[0; 9]; 9]) {
+        UniqueResult::Multiple(pair) => {
+            assert_ne!(pair[0], pair[1]);
+            assert!(pair.iter().all(is_solved));
+        }
+        other => panic!("expected two solutions, got {:?}", other),
+    }
+
+    let mut clash = [[0; 9]; 9];
+    clash[0][0] = 5;
+    clash[0][8] = 5;
+    assert_eq!(unique_solution(&clash), UniqueResult::None);
+}
+
+#[test]
+fn find_two_solutions_reports_none_for_a_dead_end() {
+    let mut clash = [[0; 9]; 9];
+    clash[4][0] = 7;
+    clash[4][5] = 7;
+    assert_eq!(find_two_solutions(&clash), TwoSolutions::Zero);
+}
+
+#[test]
+fn find_two_solutions_returns_a_unique_solution() {
+    let puzzle = sample_puzzle(Difficulty::Hard);
+    let solution = BacktrackingSudokuSolver::default().solve(&puzzle).unwrap();
+    assert_eq!(
+        find_two_solutions(&puzzle),
+        TwoSolutions::One(Box::new(solution))
+    );
+}
+
+#[test]
+fn find_two_solutions_returns_two_differing_solutions() {
+    // Blanking a solved grid's first two rows leaves column pairs to swap
+    let mut puzzle = BacktrackingSudokuSolver::default()
+        .solve(&default_puzzle())
+        .unwrap();
+    puzzle[0] = [0; 9];
+    puzzle[1] = [0; 9];
+    match find_two_solutions(&puzzle) {
+        TwoSolutions::Two(first, second) => {
+            for solution in [&first, &second] {
+                assert!(is_solved(solution));
+                assert!(respects_givens(&puzzle, solution));
+            }
+            assert!(!diff(&first, &second).is_empty());
+        }
+        other => panic!("expected two solutions, got {:?}", other),
+    }
+}
+
+#[test]
+fn values_above_nine_count_as_conflicts_instead_of_panicking() {
+    let mut puzzle = default_puzzle();
+    puzzle[2][6] = 12;
+    assert!(has_conflicts(&puzzle));
+    assert_eq!(count_solutions(&puzzle, 2), 0);
+    assert_eq!(count_solutions_until(&puzzle, 2, None), Some(0));
+    assert!(solve_all(&puzzle, 2, usize::MAX).solutions.is_empty());
+    assert_eq!(unique_solution(&puzzle), UniqueResult::None);
+    assert_eq!(find_two_solutions(&puzzle), TwoSolutions::Zero);
+}
@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use sudoku_sat::export::{to_constraint_graph, to_graded_svg, to_svg, trace_to_markdown};
+use sudoku_sat::grid::givens_mask;
+use sudoku_sat::io::to_pretty_string;
+use sudoku_sat::puzzles::default_puzzle;
+use sudoku_sat::solver::SudokuSolver;
+use sudoku_sat::solver::backtracking::BacktrackingSudokuSolver;
+use sudoku_sat::solver::logic::solve_with_trace;
+
+/// Compare `actual` with `tests/golden/<name>`; `UPDATE_GOLDEN=1` rewrites the
+/// file instead, after a deliberate rendering change
+fn assert_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+    }
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        expected == actual,
+        "{} differs from the golden file; rerun with UPDATE_GOLDEN=1 if intended",
+        name
+    );
+}
+
+#[test]
+fn svg_of_the_solved_default_puzzle_matches_golden() {
+    let puzzle = default_puzzle();
+    let solution = BacktrackingSudokuSolver::default().solve(&puzzle).unwrap();
+    assert_golden(
+        "default_solved.svg",
+        &to_svg(&solution, &givens_mask(&puzzle)),
+    );
+}
+
+#[test]
+fn constraint_graph_of_the_default_puzzle_matches_golden() {
+    let dot = to_constraint_graph(&default_puzzle());
+    // 81 cells with 20 peers each, every edge listed once
+    assert_eq!(dot.matches(" -- ").count(), 81 * 20 / 2);
+    assert_golden("default_constraints.dot", &dot);
+}
+
+#[test]
+fn graded_svg_matches_golden() {
+    let puzzle = default_puzzle();
+    let solution = BacktrackingSudokuSolver::default().solve(&puzzle).unwrap();
+    // Row 1's empty cells: the first right, the second wrong, the rest blank
+    let mut attempt = puzzle;
+    let open: Vec<usize> = (0..9).filter(|&col| puzzle[0][col] == 0).collect();
+    attempt[0][open[0]] = solution[0][open[0]];
+    attempt[0][open[1]] = solution[0][open[1]] % 9 + 1;
+    assert_golden("graded.svg", &to_graded_svg(&puzzle, &attempt, &solution));
+}
+
+#[test]
+fn trace_markdown_of_a_two_step_solve() {
+    let solution = BacktrackingSudokuSolver::default()
+        .solve(&default_puzzle())
+        .unwrap();
+    let mut puzzle = solution;
+    puzzle[0][0] = 0;
+    puzzle[8][8] = 0;
+    let steps = solve_with_trace(&puzzle);
+    assert_eq!(
+        trace_to_markdown(&steps, None),
+        format!(
+            "1. **Naked single**: R1C1 = {}\n2. **Naked single**: R9C9 = {}\n",
+            solution[0][0], solution[8][8]
+        )
+    );
+
+    // With the puzzle, each placement shows the grid after it; the last one
+    // is the solution
+    let md = trace_to_markdown(&steps, Some(&puzzle));
+    assert_eq!(md.matches("   ```\n").count(), 4);
+    let last_grid: String = to_pretty_string(&solution)
+        .lines()
+        .map(|line| format!("   {}\n", line))
+        .collect();
+    assert!(md.ends_with(&format!("{}   ```\n\n", last_grid)));
+}
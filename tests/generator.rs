@@ -0,0 +1,71 @@
+use sudoku_sat::generator::{ClueTarget, Generated, Rng, complete_to_unique, generate_with_target};
+use sudoku_sat::grid::respects_givens;
+use sudoku_sat::puzzles::default_puzzle;
+use sudoku_sat::solver::backtracking::{UniqueResult, unique_solution};
+
+#[test]
+fn complete_to_unique_keeps_the_givens_and_removes_the_ambiguity() {
+    // Thinning the default puzzle's first two rows leaves many solutions
+    let mut partial = default_puzzle();
+    for row in partial.iter_mut().take(2) {
+        *row = [0; 9];
+    }
+    assert!(matches!(
+        unique_solution(&partial),
+        UniqueResult::Multiple(_)
+    ));
+    for seed in 0..2 {
+        let puzzle = complete_to_unique(&partial, &mut Rng::new(seed)).unwrap();
+        assert!(respects_givens(&partial, &puzzle), "seed {}", seed);
+        assert!(
+            matches!(unique_solution(&puzzle), UniqueResult::Unique(_)),
+            "seed {}",
+            seed
+        );
+    }
+}
+
+#[test]
+fn complete_to_unique_leaves_unique_and_unsolvable_puzzles_alone() {
+    let mut rng = Rng::new(1);
+    let puzzle = default_puzzle();
+    assert_eq!(complete_to_unique(&puzzle, &mut rng), Some(puzzle));
+    let mut clash = [[0; 9]; 9];
+    clash[0][0] = 3;
+    clash[8][0] = 3;
+    assert_eq!(complete_to_unique(&clash, &mut rng), None);
+}
+
+fn clue_count(puzzle: &[[usize; 9]; 9]) -> usize {
+    puzzle.iter().flatten().filter(|&&d| d != 0).count()
+}
+
+#[test]
+fn both_clue_targets_hit_a_reachable_count() {
+    for target in [ClueTarget::AtMost(40), ClueTarget::Exactly(40)] {
+        match generate_with_target(target, &mut Rng::new(5)) {
+            Generated::Reached(puzzle) => {
+                assert_eq!(clue_count(&puzzle), 40, "{:?}", target);
+                assert!(matches!(unique_solution(&puzzle), UniqueResult::Unique(_)));
+            }
+            other => panic!("{:?} gave {:?}", target, other),
+        }
+    }
+}
+
+#[test]
+fn clue_targets_differ_when_removal_stalls() {
+    // No puzzle with 16 givens is unique, so removal always stalls above it
+    match generate_with_target(ClueTarget::AtMost(16), &mut Rng::new(5)) {
+        Generated::Above { puzzle, clues } => {
+            assert!(clues > 16);
+            assert_eq!(clue_count(&puzzle), clues);
+            assert!(matches!(unique_solution(&puzzle), UniqueResult::Unique(_)));
+        }
+        other => panic!("AtMost gave {:?}", other),
+    }
+    assert!(matches!(
+        generate_with_target(ClueTarget::Exactly(16), &mut Rng::new(5)),
+        Generated::Failed { clues } if clues > 16
+    ));
+}
@@ -0,0 +1,128 @@
+use sudoku_sat::grid::{
+    ErrorKind, box_cells, box_index, diff, digit_counts, find_duplicates, first_error,
+    flip_vertical, rotate_cw, satisfaction, units, variant_units,
+};
+use sudoku_sat::puzzles::default_puzzle;
+use sudoku_sat::solver::backtracking::BacktrackingSudokuSolver;
+use sudoku_sat::solver::{SudokuSolver, Variant};
+
+fn solved() -> [[usize; 9]; 9] {
+    BacktrackingSudokuSolver::default()
+        .solve(&default_puzzle())
+        .unwrap()
+}
+
+#[test]
+fn first_error_is_none_for_a_solved_grid() {
+    assert_eq!(first_error(&solved()), None);
+}
+
+#[test]
+fn first_error_reports_the_first_empty_cell() {
+    let mut grid = solved();
+    grid[4][2] = 0;
+    grid[7][7] = 0;
+    assert_eq!(first_error(&grid), Some((4, 2, ErrorKind::Empty)));
+}
+
+#[test]
+fn first_error_reports_the_first_repeated_digit() {
+    let mut grid = solved();
+    // Copying R3C1 into R3C2 repeats it in the row and leaves R3C2's old
+    // digit missing; R3C1 is the first cell in row-major order that clashes
+    grid[2][1] = grid[2][0];
+    assert_eq!(first_error(&grid), Some((2, 0, ErrorKind::Duplicate)));
+}
+
+#[test]
+fn diff_of_identical_grids_is_empty() {
+    let grid = solved();
+    assert_eq!(diff(&grid, &grid), vec![]);
+}
+
+#[test]
+fn diff_of_completely_different_grids_lists_every_cell() {
+    let a = solved();
+    // Shifting every digit by one changes all 81 cells
+    let b = a.map(|row| row.map(|d| d % 9 + 1));
+    let changes = diff(&a, &b);
+    assert_eq!(changes.len(), 81);
+    assert_eq!(changes[0], (0, 0, a[0][0], b[0][0]));
+    assert_eq!(changes[80], (8, 8, a[8][8], b[8][8]));
+}
+
+#[test]
+fn units_cover_every_cell_once_per_kind() {
+    let all = units();
+    assert_eq!(all.len(), 27);
+    // Rows, columns and boxes each partition the grid, so every cell lies in
+    // exactly one unit of each kind
+    for kind in all.chunks(9) {
+        let mut seen = [[0; 9]; 9];
+        for unit in kind {
+            for &(row, col) in unit {
+                seen[row][col] += 1;
+            }
+        }
+        assert_eq!(seen, [[1; 9]; 9]);
+    }
+    assert_eq!(variant_units(Variant::LatinSquare), &all[..18]);
+    assert_eq!(variant_units(Variant::Classic).len(), 27);
+}
+
+#[test]
+fn find_duplicates_spots_a_rotated_copy() {
+    let puzzle = default_puzzle();
+    let mut other = puzzle;
+    other[0][0] = 0;
+    other[0][1] = 0;
+    other[0][2] = 0;
+    let grids = [puzzle, other, rotate_cw(&puzzle), flip_vertical(&other)];
+    assert_eq!(find_duplicates(&grids), vec![None, None, Some(0), Some(1)]);
+}
+
+#[test]
+fn box_cells_lists_the_cells_box_index_maps_there() {
+    assert_eq!(box_index(0, 0), 0);
+    assert_eq!(box_index(4, 7), 5);
+    assert_eq!(box_index(8, 8), 8);
+    assert_eq!(box_cells(5)[0], (3, 6));
+    assert_eq!(box_cells(5)[8], (5, 8));
+    for b in 0..9 {
+        let cells = box_cells(b);
+        assert!(cells.iter().all(|&(row, col)| box_index(row, col) == b));
+        // Row-major within the box
+        let mut sorted = cells;
+        sorted.sort();
+        assert_eq!(sorted, cells);
+    }
+}
+
+#[test]
+fn digit_counts_tallies_complete_and_partial_grids() {
+    assert_eq!(digit_counts(&solved()), [0, 9, 9, 9, 9, 9, 9, 9, 9, 9]);
+    assert_eq!(digit_counts(&[[0; 9]; 9]), [81, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let mut partial = [[0; 9]; 9];
+    partial[0][0] = 3;
+    partial[4][4] = 3;
+    partial[8][8] = 7;
+    // Values above 9 are skipped, not counted or panicked on
+    partial[2][2] = 12;
+    assert_eq!(digit_counts(&partial), [77, 0, 0, 2, 0, 0, 0, 1, 0, 0]);
+}
+
+#[test]
+fn satisfaction_is_full_for_clean_partials_and_drops_per_dirty_unit() {
+    assert_eq!(satisfaction(&[[0; 9]; 9]), 1.0);
+    assert_eq!(satisfaction(&default_puzzle()), 1.0);
+    assert_eq!(satisfaction(&solved()), 1.0);
+
+    let mut grid = [[0; 9]; 9];
+    grid[0][0] = 5;
+    grid[0][8] = 5; // row 1
+    assert_eq!(satisfaction(&grid), 26.0 / 27.0);
+    grid[1][1] = 5; // and box 1
+    assert_eq!(satisfaction(&grid), 25.0 / 27.0);
+    grid[8][0] = 5; // and column 1
+    assert_eq!(satisfaction(&grid), 24.0 / 27.0);
+}
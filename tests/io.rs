@@ -0,0 +1,172 @@
+use sudoku_sat::io::{
+    GridKey, ParseError, ParseOptions, pack, parse_line, parse_line_order, parse_line_with,
+    parse_puzzles, read_opensudoku, sdm_puzzles, to_line, to_line_order, to_pretty_string, unpack,
+};
+use sudoku_sat::puzzles::default_puzzle;
+
+/// A valid 16x16 grid: each row is the previous one shifted within its band
+fn grid_16() -> Vec<Vec<usize>> {
+    (0..16)
+        .map(|r| {
+            (0..16)
+                .map(|c| (4 * (r % 4) + r / 4 + c) % 16 + 1)
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn round_trips_16x16_hex_labels() {
+    let grid = grid_16();
+    let line = to_line_order(&grid, 4);
+    assert_eq!(line.len(), 256);
+    assert!(line.starts_with("0123456789ABCDEF4567"));
+    assert_eq!(parse_line_order(&line, 4).unwrap(), grid);
+}
+
+#[test]
+fn round_trips_16x16_with_empty_cells_and_separators() {
+    let mut grid = grid_16();
+    grid[0][0] = 0;
+    grid[15][15] = 0;
+    let line = to_line_order(&grid, 4);
+    assert_eq!(&line[..1], ".");
+    // Row-per-line layout with box separators parses back the same
+    let pretty: Vec<String> = line
+        .as_bytes()
+        .chunks(16)
+        .map(|row| {
+            let row = std::str::from_utf8(row).unwrap();
+            format!(
+                "{}|{}|{}|{}",
+                &row[..4],
+                &row[4..8],
+                &row[8..12],
+                &row[12..]
+            )
+        })
+        .collect();
+    assert_eq!(parse_line_order(&pretty.join("\n"), 4).unwrap(), grid);
+}
+
+#[test]
+fn sdm_collections_keep_the_name_and_skip_comment_lines() {
+    let line = to_line(&default_puzzle());
+    let text = format!("# Name: Practice\n{}\n# second half\n\n{}\n", line, line);
+    let puzzles = parse_puzzles(&text).unwrap();
+    assert_eq!(puzzles.len(), 2);
+    for puzzle in &puzzles {
+        assert_eq!(puzzle.name.as_deref(), Some("Practice"));
+        assert_eq!(puzzle.grid, default_puzzle());
+    }
+
+    // `--batch` streams the same file and reports the original line numbers
+    let lines: Vec<usize> = sdm_puzzles(text.as_bytes())
+        .map(|(line, puzzle)| {
+            assert!(puzzle.is_ok());
+            line
+        })
+        .collect();
+    assert_eq!(lines, [2, 5]);
+}
+
+#[test]
+fn pack_round_trips_every_cell_value() {
+    // Every value a cell can hold, 0 (empty) through 9, in both nibbles
+    let mut grid = [[0; 9]; 9];
+    for i in 0..81 {
+        grid[i / 9][i % 9] = (i + 1) % 10;
+    }
+    let bytes = pack(&grid);
+    assert_eq!(unpack(&bytes), grid);
+    // Cell 0 is the low nibble of byte 0; cell 80 sits alone in the last
+    // byte, whose high nibble is padding
+    assert_eq!(bytes[0], 0x21);
+    assert_eq!(bytes[40], 0x01);
+}
+
+#[test]
+fn grid_keys_are_equal_exactly_when_the_grids_are() {
+    let puzzle = default_puzzle();
+    let mut other = puzzle;
+    other[8][8] = if other[8][8] == 0 { 1 } else { 0 };
+    assert_eq!(GridKey::from(&puzzle), GridKey::from(puzzle));
+    assert_ne!(GridKey::from(&puzzle), GridKey::from(&other));
+
+    let key = GridKey::from(&puzzle);
+    assert_eq!(key.as_str(), to_line(&puzzle));
+    assert_eq!(key.to_grid(), Ok(puzzle));
+}
+
+#[test]
+fn parse_options_reject_exactly_what_they_disallow() {
+    let puzzle = default_puzzle();
+    let dots = to_line(&puzzle);
+    let zeros = dots.replace('.', "0");
+    let pretty = to_pretty_string(&puzzle);
+    for bits in 0..8 {
+        let opts = ParseOptions {
+            allow_zero: bits & 1 != 0,
+            allow_dot: bits & 2 != 0,
+            allow_whitespace: bits & 4 != 0,
+        };
+        let parses = |line: &str| parse_line_with(line, &opts).map(|_| ());
+        let rejects = |index: usize, ch: char| Err(ParseError::InvalidChar { index, ch });
+        let first_dot = dots.find('.').unwrap();
+        assert_eq!(
+            parses(&dots),
+            if opts.allow_dot {
+                Ok(())
+            } else {
+                rejects(first_dot, '.')
+            },
+            "{:?}",
+            opts
+        );
+        assert_eq!(
+            parses(&zeros),
+            if opts.allow_zero {
+                Ok(())
+            } else {
+                rejects(first_dot, '0')
+            },
+            "{:?}",
+            opts
+        );
+        // The pretty layout mixes separators and dots; the first one that is
+        // disallowed is reported
+        let expected = match pretty.char_indices().find(|&(_, ch)| {
+            (ch == '.' && !opts.allow_dot) || (ch == ' ' && !opts.allow_whitespace)
+        }) {
+            Some((index, ch)) => rejects(index, ch),
+            None => Ok(()),
+        };
+        assert_eq!(parses(&pretty), expected, "{:?}", opts);
+        if opts.allow_dot && opts.allow_zero && opts.allow_whitespace {
+            assert_eq!(opts, ParseOptions::default());
+        }
+    }
+}
+
+#[test]
+fn reads_an_opensudoku_collection() {
+    let xml = include_str!("data/sample.opensudoku");
+    let puzzles = read_opensudoku(xml.as_bytes()).unwrap();
+    let expected = [
+        (
+            Some("Warm & easy"),
+            ".36...9..1..53.2....4.....6.47....53.....8.6969..4.......8.7..1..2.....4.85....2.",
+        ),
+        (
+            None,
+            "9....8..23159......8..3...6.....37.9..321..854..79..3...2......14.3.....8..1...23",
+        ),
+    ];
+    assert_eq!(puzzles.len(), expected.len());
+    for (puzzle, (name, line)) in puzzles.iter().zip(expected) {
+        assert_eq!(puzzle.name.as_deref(), name);
+        assert_eq!(puzzle.grid, parse_line(line).unwrap());
+    }
+    // `--file` picks the format up from the leading `<`
+    assert_eq!(parse_puzzles(xml).unwrap(), puzzles);
+}
@@ -0,0 +1,246 @@
+use sudoku_sat::grid::{flip_horizontal, rotate_cw};
+use sudoku_sat::io::parse_line;
+use sudoku_sat::puzzles::sample_puzzle;
+use sudoku_sat::solver::SudokuSolver;
+use sudoku_sat::solver::backtracking::BacktrackingSudokuSolver;
+use sudoku_sat::solver::logic::{
+    Candidates, Difficulty, Hint, RatingCache, SolveStep, Technique, Unit, best_hint,
+    estimate_difficulty, find_x_wing, naked_forced, next_step, solve_logic_only,
+    trivial_fill_count,
+};
+
+#[test]
+fn cached_ratings_equal_fresh_estimates() {
+    let mut cache = RatingCache::new(16);
+    for difficulty in Difficulty::ALL {
+        let puzzle = sample_puzzle(difficulty);
+        let fresh = estimate_difficulty(&puzzle);
+        assert_eq!(fresh, Some(difficulty));
+        assert_eq!(cache.rating(&puzzle), fresh, "{}", difficulty);
+        assert_eq!(cache.rating(&puzzle), fresh, "{}", difficulty);
+    }
+}
+
+#[test]
+fn symmetric_copies_are_rated_as_they_stand() {
+    let mut cache = RatingCache::new(16);
+    let puzzle = sample_puzzle(Difficulty::Hard);
+    assert!(cache.is_empty());
+    cache.rating(&puzzle);
+    cache.rating(&puzzle);
+    assert_eq!(cache.len(), 1);
+    // A rotation or reflection can need a different number of guesses, so
+    // each gets an entry of its own holding its own fresh rating
+    for (i, copy) in [rotate_cw(&puzzle), flip_horizontal(&puzzle)]
+        .iter()
+        .enumerate()
+    {
+        assert_eq!(cache.rating(copy), estimate_difficulty(copy));
+        assert_eq!(cache.len(), i + 2);
+    }
+}
+
+#[test]
+fn an_evicted_rating_is_recomputed_to_the_same_value() {
+    let mut cache = RatingCache::new(1);
+    let easy = sample_puzzle(Difficulty::Easy);
+    let hard = sample_puzzle(Difficulty::Hard);
+    let expected = estimate_difficulty(&easy);
+    assert_eq!(cache.rating(&easy), expected);
+    cache.rating(&hard);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.rating(&easy), expected);
+}
+
+#[test]
+fn solve_logic_only_fills_easy_puzzles_and_gives_up_on_guessing() {
+    let easy = sample_puzzle(Difficulty::Easy);
+    let expected = BacktrackingSudokuSolver::default().solve(&easy).unwrap();
+    assert_eq!(solve_logic_only(&easy), Some(expected));
+    assert_eq!(solve_logic_only(&sample_puzzle(Difficulty::Expert)), None);
+}
+
+#[test]
+fn trivial_fill_count_counts_naked_single_placements() {
+    let solution = BacktrackingSudokuSolver::default()
+        .solve(&sample_puzzle(Difficulty::Easy))
+        .unwrap();
+    assert_eq!(trivial_fill_count(&solution), 0);
+    // Each blanked cell is the only gap in its row, so it is a naked single
+    let mut gaps = solution;
+    for i in 0..3 {
+        gaps[i * 3][i * 4] = 0;
+    }
+    assert_eq!(trivial_fill_count(&gaps), 3);
+    // Nothing is forced in an empty grid
+    assert_eq!(trivial_fill_count(&[[0; 9]; 9]), 0);
+}
+
+#[test]
+fn incremental_candidates_match_a_full_recompute() {
+    let mut grid = sample_puzzle(Difficulty::Medium);
+    let solution = BacktrackingSudokuSolver::default().solve(&grid).unwrap();
+    let (given_row, given_col) = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .find(|&(r, c)| grid[r][c] != 0)
+        .unwrap();
+    let empty: Vec<_> = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(r, c)| grid[r][c] == 0)
+        .collect();
+    let mut cands = Candidates::from_grid(&grid);
+    // Place correct digits, then overwrite one, clear another and a given
+    let edits = [
+        (empty[0], solution[empty[0].0][empty[0].1]),
+        (empty[1], solution[empty[1].0][empty[1].1]),
+        (empty[0], solution[empty[0].0][empty[0].1] % 9 + 1),
+        (empty[1], 0),
+        ((given_row, given_col), 0),
+    ];
+    for ((row, col), digit) in edits {
+        grid[row][col] = digit;
+        cands.refresh(&grid, row, col);
+        assert_eq!(
+            cands,
+            Candidates::from_grid(&grid),
+            "R{}C{}={}",
+            row + 1,
+            col + 1,
+            digit
+        );
+    }
+    // `place` is the cheaper update for a digit going into an empty cell
+    let (row, col) = empty[2];
+    let mut placed = Candidates::from_grid(&grid);
+    grid[row][col] = solution[row][col];
+    placed.place(row, col, grid[row][col]);
+    assert_eq!(placed, Candidates::from_grid(&grid));
+}
+
+#[test]
+fn naked_forced_lists_only_correct_free_moves() {
+    // Easy and hard puzzles alike have a few free moves to start with
+    for difficulty in [Difficulty::Easy, Difficulty::Hard] {
+        let puzzle = sample_puzzle(difficulty);
+        let solution = BacktrackingSudokuSolver::default().solve(&puzzle).unwrap();
+        let forced = naked_forced(&puzzle);
+        assert!(!forced.is_empty(), "{}", difficulty);
+        for &(row, col, digit) in &forced {
+            assert_eq!(puzzle[row][col], 0, "{}", difficulty);
+            assert_eq!(digit, solution[row][col], "{}", difficulty);
+        }
+        // Row-major with each cell once
+        assert!(
+            forced
+                .windows(2)
+                .all(|w| (w[0].0, w[0].1) < (w[1].0, w[1].1))
+        );
+    }
+}
+
+#[test]
+fn naked_forced_finds_a_lone_gap_and_nothing_in_a_full_grid() {
+    let solution = BacktrackingSudokuSolver::default()
+        .solve(&sample_puzzle(Difficulty::Hard))
+        .unwrap();
+    assert!(naked_forced(&solution).is_empty());
+    let mut gap = solution;
+    gap[4][7] = 0;
+    assert_eq!(naked_forced(&gap), [(4, 7, solution[4][7])]);
+    assert!(naked_forced(&[[0; 9]; 9]).is_empty());
+}
+
+#[test]
+fn x_wing_rules_seven_out_of_its_two_columns() {
+    // SudokuWiki's first X-Wing example: once the singles run out, 7 fits
+    // only in columns 4 and 8 of rows 2 and 6
+    let mut grid = parse_line(
+        "1.....569492.561.8.561.924...964.8.1.64.1....218.356.4.4.5...169.5.614.2621.....5",
+    )
+    .unwrap();
+    let mut cands = Candidates::from_grid(&grid);
+    while let Some(SolveStep::Place {
+        row, col, digit, ..
+    }) = next_step(&grid, &cands)
+    {
+        grid[row][col] = digit;
+        cands.place(row, col, digit);
+    }
+
+    let mut eliminated = Vec::new();
+    while let Some(step) = find_x_wing(&grid, &cands) {
+        let SolveStep::Eliminate {
+            cell,
+            digits,
+            technique,
+        } = step
+        else {
+            panic!("expected an elimination, got {}", step);
+        };
+        assert_eq!(digits, [7]);
+        assert_eq!(
+            technique,
+            Technique::XWing {
+                digit: 7,
+                lines: [Unit::Row(1), Unit::Row(5)],
+            }
+        );
+        assert!(cands.remove(cell.0, cell.1, 7));
+        eliminated.push(cell);
+    }
+    let expected = [
+        (0, 3),
+        (3, 7),
+        (4, 3),
+        (4, 7),
+        (7, 3),
+        (7, 7),
+        (8, 3),
+        (8, 7),
+    ];
+    assert_eq!(eliminated, expected);
+}
+
+#[test]
+fn hints_look_past_an_x_wing_and_honour_ruled_out_candidates() {
+    // The X-Wing grid above, stuck once its singles are placed
+    let mut grid = parse_line(
+        "1.....569492.561.8.561.924...964.8.1.64.1....218.356.4.4.5...169.5.614.2621.....5",
+    )
+    .unwrap();
+    let mut cands = Candidates::from_grid(&grid);
+    while let Some(SolveStep::Place {
+        row, col, digit, ..
+    }) = next_step(&grid, &cands)
+    {
+        grid[row][col] = digit;
+        cands.place(row, col, digit);
+    }
+    let solution = BacktrackingSudokuSolver::default().solve(&grid).unwrap();
+    let x_wing = Technique::XWing {
+        digit: 7,
+        lines: [Unit::Row(1), Unit::Row(5)],
+    };
+
+    let hint = best_hint(&grid, &cands).unwrap();
+    assert_eq!(hint.after, Some(x_wing));
+    assert_eq!(solution[hint.row][hint.col], hint.digit);
+    assert!(
+        hint.to_string()
+            .ends_with(&format!("once the {} is applied", x_wing))
+    );
+
+    // With the X-Wing's eliminations already made, the same placement needs nothing first
+    while let Some(SolveStep::Eliminate { cell, digits, .. }) = find_x_wing(&grid, &cands) {
+        for d in digits {
+            cands.remove(cell.0, cell.1, d);
+        }
+    }
+    assert_eq!(
+        best_hint(&grid, &cands),
+        Some(Hint {
+            after: None,
+            ..hint
+        })
+    );
+}
@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use rustsat::types::{Lit, Var};
+use sudoku_sat::grid::is_solved;
+use sudoku_sat::puzzles::{default_puzzle, sample_puzzle};
+use sudoku_sat::solver::backtracking::BacktrackingSudokuSolver;
+use sudoku_sat::solver::logic::Difficulty;
+use sudoku_sat::solver::sat::{
+    ClueOrder, ModelError, Redundancy, SatSudokuSolver, SearchControls, assignment_to_grid,
+    build_instance, cell_var, explain_unsat, repair_fewest_changes, repair_instance, var_label,
+};
+use sudoku_sat::solver::{GreaterThan, SolveError, SudokuSolver, Variant};
+
+#[test]
+fn clause_estimate_matches_the_built_encoding_for_every_configuration() {
+    for variant in [Variant::Classic, Variant::LatinSquare] {
+        for redundancy in Redundancy::combinations() {
+            let solver = SatSudokuSolver::default()
+                .with_variant(variant)
+                .with_redundancy(redundancy);
+            let built = solver.encoding_info(&[[0; 9]; 9]).total_clauses();
+            assert_eq!(
+                solver.estimated_clause_count(),
+                built,
+                "{:?} with {}",
+                variant,
+                redundancy
+            );
+        }
+    }
+}
+
+#[test]
+fn variant_clauses_count_against_the_budget() {
+    let mut solver = SatSudokuSolver::default();
+    let budget = solver.estimated_clause_count() + 44;
+    solver = solver.with_clause_budget(budget);
+    // 45 clauses for a single relation tip it over, before the backend runs
+    let relation = GreaterThan {
+        a: (0, 0),
+        b: (0, 1),
+    };
+    assert!(matches!(
+        solver.solve_with_inequalities(&[[0; 9]; 9], &[relation]),
+        Err(SolveError::ClauseBudgetExceeded(_))
+    ));
+}
+
+#[test]
+fn out_of_range_preferences_are_errors_not_panics() {
+    for bad in [(0, 0, 0), (0, 0, 10), (9, 0, 1), (0, 9, 1)] {
+        let result = SatSudokuSolver::default().solve_preferring(&[[0; 9]; 9], &[bad]);
+        assert!(
+            matches!(result, Err(SolveError::InvalidPlacement { .. })),
+            "{:?}",
+            bad
+        );
+    }
+}
+
+#[test]
+fn an_expired_timeout_interrupts_instead_of_reporting_no_solution() {
+    // The terminator fires on the backend's first check, before it can finish
+    let result = SatSudokuSolver::default()
+        .with_timeout(Duration::ZERO)
+        .solve(&sample_puzzle(Difficulty::Expert));
+    assert_eq!(result, Err(SolveError::Interrupted));
+}
+
+#[test]
+fn assignment_to_grid_names_every_cell_without_exactly_one_digit() {
+    let solution = BacktrackingSudokuSolver::default()
+        .solve(&default_puzzle())
+        .unwrap();
+    let (_, literals) = build_instance(&[[0; 9]; 9]);
+    let model_of = |grid: &[[usize; 9]; 9], extra: &[(usize, usize, usize)]| {
+        (0..729)
+            .map(|i| {
+                let (row, col, d) = (i / 81, i / 9 % 9, i % 9 + 1);
+                let lit: Lit = literals[row][col][d - 1];
+                if grid[row][col] == d || extra.contains(&(row, col, d)) {
+                    lit
+                } else {
+                    !lit
+                }
+            })
+            .collect()
+    };
+    assert_eq!(
+        assignment_to_grid(&model_of(&solution, &[]), &literals),
+        Ok(solution)
+    );
+
+    // R1C1 gets a second digit and R2C2 loses its only one
+    let mut crafted = solution;
+    crafted[1][1] = 0;
+    let extra = (solution[0][0] % 9) + 1;
+    let mut both = vec![solution[0][0], extra];
+    both.sort();
+    assert_eq!(
+        assignment_to_grid(&model_of(&crafted, &[(0, 0, extra)]), &literals),
+        Err(ModelError {
+            cells: vec![(0, 0, both), (1, 1, vec![])],
+        })
+    );
+}
+
+#[test]
+fn an_empty_puzzle_solves_without_panicking() {
+    let solution = SatSudokuSolver::default().solve(&[[0; 9]; 9]).unwrap();
+    assert!(is_solved(&solution));
+}
+
+#[test]
+fn repair_changes_one_of_two_clashing_givens() {
+    let mut puzzle = default_puzzle();
+    let (row, col) = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .find(|&(row, col)| puzzle[row][col] == 0)
+        .unwrap();
+    // Copy a given from elsewhere in the row into an empty cell of it
+    let digit = *puzzle[row].iter().find(|&&d| d != 0).unwrap();
+    puzzle[row][col] = digit;
+    let (changes, repaired) = repair_fewest_changes(&puzzle).unwrap();
+    assert_eq!(changes, 1);
+    assert!(is_solved(&repaired));
+}
+
+#[test]
+fn repair_rejects_cells_outside_0_to_9() {
+    let mut puzzle = default_puzzle();
+    puzzle[2][3] = 12;
+    let invalid = SolveError::InvalidDigit {
+        row: 2,
+        col: 3,
+        digit: 12,
+    };
+    assert_eq!(repair_fewest_changes(&puzzle), Err(invalid.clone()));
+    assert!(matches!(repair_instance(&puzzle), Err(e) if e == invalid));
+}
+
+#[test]
+fn explain_unsat_returns_a_minimal_core() {
+    // R1C1 is left without a candidate: its row holds 1-8 and its column a 9.
+    // The 5 in R9C9 plays no part
+    let mut puzzle = [[0; 9]; 9];
+    for col in 1..9 {
+        puzzle[0][col] = col;
+    }
+    puzzle[5][0] = 9;
+    puzzle[8][8] = 5;
+    let core = explain_unsat(&puzzle).unwrap().unwrap();
+    let mut expected: Vec<(usize, usize)> = (1..9).map(|col| (0, col)).collect();
+    expected.push((5, 0));
+    assert_eq!(core, expected);
+
+    // Only the core's givens are needed, and dropping any one of them leaves
+    // a solvable puzzle
+    let keep = |cells: &[(usize, usize)]| {
+        let mut grid = [[0; 9]; 9];
+        for &(row, col) in cells {
+            grid[row][col] = puzzle[row][col];
+        }
+        grid
+    };
+    let mut solver = BacktrackingSudokuSolver::default();
+    assert_eq!(solver.is_satisfiable(&keep(&core)), Ok(false));
+    for i in 0..core.len() {
+        let mut rest = core.clone();
+        rest.remove(i);
+        assert_eq!(
+            solver.is_satisfiable(&keep(&rest)),
+            Ok(true),
+            "{:?}",
+            core[i]
+        );
+    }
+}
+
+#[test]
+fn explain_unsat_rejects_cells_outside_0_to_9() {
+    assert_eq!(explain_unsat(&default_puzzle()), Ok(None));
+    let mut puzzle = default_puzzle();
+    puzzle[0][0] = 10;
+    assert_eq!(
+        explain_unsat(&puzzle),
+        Err(SolveError::InvalidDigit {
+            row: 0,
+            col: 0,
+            digit: 10
+        })
+    );
+}
+
+#[test]
+fn solve_assuming_clues_rejects_cells_outside_0_to_9() {
+    let mut puzzle = default_puzzle();
+    puzzle[4][4] = 11;
+    for order in [ClueOrder::RowMajor, ClueOrder::MostConstrained] {
+        assert_eq!(
+            SatSudokuSolver::default()
+                .with_clue_order(order)
+                .solve_assuming_clues(&puzzle),
+            Err(SolveError::InvalidDigit {
+                row: 4,
+                col: 4,
+                digit: 11
+            })
+        );
+    }
+}
+
+#[test]
+fn var_labels_are_unique_and_name_their_cell_var() {
+    let mut seen = HashSet::new();
+    for row in 0..9 {
+        for col in 0..9 {
+            for digit in 1..=9 {
+                let var = cell_var(row, col, digit);
+                let label = var_label(var).unwrap();
+                assert_eq!(label, format!("R{}C{}={}", row + 1, col + 1, digit));
+                assert!(seen.insert(label));
+            }
+        }
+    }
+    assert_eq!(seen.len(), 729);
+    // Auxiliary variables past the cell block have no label
+    assert_eq!(var_label(Var::new(729)), None);
+}
+
+#[test]
+fn a_bad_preferred_placement_is_an_error() {
+    for (row, col, digit) in [(9, 0, 1), (0, 0, 0), (0, 0, 10)] {
+        let mut solver = SatSudokuSolver::default().with_search_controls(SearchControls {
+            preferred: vec![(row, col, digit)],
+            ..SearchControls::default()
+        });
+        assert_eq!(
+            solver.solve(&default_puzzle()),
+            Err(SolveError::InvalidPlacement { row, col, digit })
+        );
+    }
+}
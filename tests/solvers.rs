@@ -0,0 +1,402 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sudoku_sat::generator::{Rng, generate};
+use sudoku_sat::grid::{is_solved, respects_givens};
+use sudoku_sat::io::to_line;
+use sudoku_sat::puzzles::{default_puzzle, sample_puzzle};
+use sudoku_sat::solver::auto::AutoSudokuSolver;
+use sudoku_sat::solver::backtracking::{self, BacktrackingSudokuSolver};
+use sudoku_sat::solver::check::{determinism, round_trip};
+use sudoku_sat::solver::exact_cover::ExactCoverSudokuSolver;
+use sudoku_sat::solver::logic::Difficulty;
+use sudoku_sat::solver::sat::SatSudokuSolver;
+use sudoku_sat::solver::{
+    GreaterThan, Parity, SolveError, SolverKind, SudokuSolver, Variant, make_solver,
+};
+
+#[test]
+fn every_solver_returns_the_same_grid_for_the_default_puzzle() {
+    let puzzle = default_puzzle();
+    let solutions: Vec<_> = SolverKind::ALL
+        .into_iter()
+        .map(|kind| (kind, make_solver(kind).solve(&puzzle).unwrap()))
+        .collect();
+    let (_, expected) = solutions[0];
+    assert!(is_solved(&expected));
+    assert!(respects_givens(&puzzle, &expected));
+    for (kind, solution) in &solutions {
+        assert_eq!(solution, &expected, "{:?} disagrees", kind);
+    }
+}
+
+#[test]
+fn exact_cover_matches_backtracking_on_every_sample() {
+    for difficulty in Difficulty::ALL {
+        let puzzle = sample_puzzle(difficulty);
+        let expected = BacktrackingSudokuSolver::default().solve(&puzzle).unwrap();
+        let solution = ExactCoverSudokuSolver::default().solve(&puzzle).unwrap();
+        assert_eq!(solution, expected, "{}", difficulty);
+    }
+}
+
+#[test]
+fn exact_cover_rejects_clashing_givens() {
+    let mut puzzle = [[0; 9]; 9];
+    puzzle[0][0] = 5;
+    puzzle[1][1] = 5;
+    assert_eq!(
+        ExactCoverSudokuSolver::default().solve(&puzzle),
+        Err(SolveError::Unsatisfiable)
+    );
+}
+
+/// Panic unless every row and column of `grid` holds each digit 1-9 once
+fn assert_latin_square(grid: &[[usize; 9]; 9]) {
+    for i in 0..9 {
+        let mut row: Vec<usize> = grid[i].to_vec();
+        let mut col: Vec<usize> = (0..9).map(|r| grid[r][i]).collect();
+        row.sort();
+        col.sort();
+        assert_eq!(row, (1..=9).collect::<Vec<_>>());
+        assert_eq!(col, (1..=9).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn exact_cover_drops_the_box_constraints_for_latin_squares() {
+    // Two 1s in the top-left box: fine in a Latin square, not in classic Sudoku
+    let mut puzzle = [[0; 9]; 9];
+    puzzle[0][0] = 1;
+    puzzle[1][1] = 1;
+    let solution = ExactCoverSudokuSolver::default()
+        .with_variant(Variant::LatinSquare)
+        .solve(&puzzle)
+        .unwrap();
+    assert!(respects_givens(&puzzle, &solution));
+    assert_latin_square(&solution);
+}
+
+#[test]
+fn sat_and_backtracking_allow_box_repeats_in_latin_squares() {
+    let mut puzzle = [[0; 9]; 9];
+    puzzle[0][0] = 1;
+    puzzle[1][1] = 1;
+    for kind in [SolverKind::Sat, SolverKind::Backtracking] {
+        let solution = make_solver(kind)
+            .with_variant(Variant::LatinSquare)
+            .solve(&puzzle)
+            .unwrap();
+        assert!(respects_givens(&puzzle, &solution), "{:?}", kind);
+        assert_latin_square(&solution);
+    }
+}
+
+#[test]
+fn exact_cover_stops_at_the_node_limit() {
+    assert_eq!(
+        ExactCoverSudokuSolver::default()
+            .with_max_nodes(10)
+            .solve(&[[0; 9]; 9]),
+        Err(SolveError::NodeLimitExceeded { limit: 10 })
+    );
+}
+
+#[test]
+fn every_solver_round_trips_generated_puzzles() {
+    assert_eq!(round_trip(7, 3, 30), Ok(()));
+}
+
+#[test]
+fn complete_grids_are_checked_instead_of_searched() {
+    let puzzle = default_puzzle();
+    let solution = BacktrackingSudokuSolver::default().solve(&puzzle).unwrap();
+    // Swapping two cells of a row keeps the row valid but breaks two columns
+    let mut broken = solution;
+    broken[0].swap(0, 1);
+    for kind in SolverKind::ALL {
+        let mut solver = make_solver(kind);
+        assert_eq!(solver.solve(&solution), Ok(solution), "{:?}", kind);
+        assert_eq!(
+            solver.solve(&broken),
+            Err(SolveError::Unsatisfiable),
+            "{:?}",
+            kind
+        );
+        // A partial grid still goes through the search
+        assert_eq!(solver.solve(&puzzle), Ok(solution), "{:?}", kind);
+    }
+}
+
+#[test]
+fn exact_cover_and_sat_agree_on_generated_puzzles() {
+    for seed in 1..=5 {
+        let puzzle = generate(28, &mut Rng::new(seed));
+        let expected = SatSudokuSolver::default().solve(&puzzle).unwrap();
+        let solution = ExactCoverSudokuSolver::default().solve(&puzzle).unwrap();
+        assert_eq!(solution, expected, "seed {}: {}", seed, to_line(&puzzle));
+    }
+}
+
+/// A SAT stage whose backend always fails
+struct BrokenBackend;
+
+impl SudokuSolver for BrokenBackend {
+    fn solve(&mut self, _: &[[usize; 9]; 9]) -> Result<[[usize; 9]; 9], SolveError> {
+        Err(SolveError::SolverBackendError("injected failure".into()))
+    }
+}
+
+#[test]
+fn auto_falls_back_to_backtracking_when_the_backend_fails() {
+    // Needs guessing, so logic can't finish it, and no node budget lets the
+    // backtracking stage finish it either
+    let puzzle = sample_puzzle(Difficulty::Expert);
+    let reasons = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&reasons);
+    let mut solver = AutoSudokuSolver::default()
+        .with_max_nodes(0)
+        .with_sat_solver(BrokenBackend)
+        .with_fallback_hook(Box::new(move |reason| {
+            log.borrow_mut().push(reason.to_string())
+        }));
+
+    let solution = solver.solve(&puzzle).unwrap();
+    assert!(is_solved(&solution));
+    assert!(respects_givens(&puzzle, &solution));
+    assert_eq!(solver.fallback_reason(), Some("injected failure"));
+    assert_eq!(*reasons.borrow(), ["injected failure"]);
+
+    // A solve that never reaches the SAT stage clears the reason
+    solver.solve(&default_puzzle()).unwrap();
+    assert_eq!(solver.fallback_reason(), None);
+}
+
+#[test]
+fn restrictions_steer_both_solvers_away_from_the_plain_solution() {
+    let empty = [[0; 9]; 9];
+    let plain = BacktrackingSudokuSolver::default().solve(&empty).unwrap();
+    // Any digit but the one the plain solve puts in R1C1
+    let digits: Vec<usize> = (1..=9).filter(|&d| d != plain[0][0]).collect();
+    let restrictions = [((0, 0), digits.clone())];
+    let results = [
+        backtracking::solve_with_restrictions(&empty, &restrictions),
+        SatSudokuSolver::default().solve_with_restrictions(&empty, &restrictions),
+    ];
+    for solution in results {
+        let solution = solution.unwrap();
+        assert!(is_solved(&solution));
+        assert!(digits.contains(&solution[0][0]));
+        assert_ne!(solution, plain);
+    }
+}
+
+#[test]
+fn restrictions_off_the_grid_or_outside_1_to_9_are_errors() {
+    let puzzle = default_puzzle();
+    for (cell, digits, digit) in [
+        ((9, 0), vec![3], 3),
+        ((0, 9), vec![], 0),
+        ((4, 4), vec![2, 0], 0),
+        ((4, 4), vec![16], 16),
+    ] {
+        let expected = Err(SolveError::InvalidPlacement {
+            row: cell.0,
+            col: cell.1,
+            digit,
+        });
+        let restrictions = [(cell, digits)];
+        assert_eq!(
+            backtracking::solve_with_restrictions(&puzzle, &restrictions),
+            expected
+        );
+        assert_eq!(
+            SatSudokuSolver::default().solve_with_restrictions(&puzzle, &restrictions),
+            expected
+        );
+    }
+}
+
+#[test]
+fn every_solver_reports_satisfiability() {
+    // R1C1 can't hold anything: its row has 1-8 and its column a 9
+    let mut dead_end = [[0; 9]; 9];
+    for col in 1..9 {
+        dead_end[0][col] = col;
+    }
+    dead_end[5][0] = 9;
+    for kind in SolverKind::ALL {
+        let mut solver = make_solver(kind);
+        assert_eq!(
+            solver.is_satisfiable(&default_puzzle()),
+            Ok(true),
+            "{:?}",
+            kind
+        );
+        assert_eq!(solver.is_satisfiable(&dead_end), Ok(false), "{:?}", kind);
+    }
+}
+
+#[test]
+fn every_solver_is_deterministic() {
+    assert_eq!(determinism(&default_puzzle(), 3), Ok(()));
+}
+
+#[test]
+fn a_chain_of_inequalities_orders_the_first_row() {
+    // R1C1 > R1C2 > ... > R1C9 leaves only 9, 8, ..., 1
+    let chain: Vec<GreaterThan> = (0..8)
+        .map(|col| GreaterThan {
+            a: (0, col),
+            b: (0, col + 1),
+        })
+        .collect();
+    let empty = [[0; 9]; 9];
+    let results = [
+        backtracking::solve_with_inequalities(&empty, &chain),
+        SatSudokuSolver::default().solve_with_inequalities(&empty, &chain),
+    ];
+    for solution in results {
+        let solution = solution.unwrap();
+        assert!(is_solved(&solution));
+        assert_eq!(solution[0], [9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    }
+}
+
+#[test]
+fn inequalities_off_the_grid_are_errors() {
+    let relation = GreaterThan {
+        a: (0, 0),
+        b: (3, 9),
+    };
+    let expected = Err(SolveError::InvalidPlacement {
+        row: 3,
+        col: 9,
+        digit: 0,
+    });
+    let puzzle = default_puzzle();
+    assert_eq!(
+        backtracking::solve_with_inequalities(&puzzle, &[relation]),
+        expected
+    );
+    assert_eq!(
+        SatSudokuSolver::default().solve_with_inequalities(&puzzle, &[relation]),
+        expected
+    );
+    // Checking a placement against it treats the missing cell as empty
+    assert!(relation.allows(&puzzle, 0, 0, 1));
+}
+
+#[test]
+fn solve_flat_checks_the_buffer_length_and_passes_errors_through() {
+    let puzzle = default_puzzle();
+    let flat: Vec<usize> = puzzle.into_iter().flatten().collect();
+    for kind in SolverKind::ALL {
+        let mut solver = make_solver(kind);
+        let expected: Vec<usize> = solver
+            .solve(&puzzle)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(solver.solve_flat(&flat), Ok(expected), "{:?}", kind);
+        for len in [0, 80, 82] {
+            let mut buffer = flat.clone();
+            buffer.resize(len, 0);
+            assert_eq!(
+                solver.solve_flat(&buffer),
+                Err(SolveError::WrongLength {
+                    expected: 81,
+                    found: len
+                }),
+                "{:?}",
+                kind
+            );
+        }
+        let mut bad = flat.clone();
+        bad[10] = 12;
+        assert_eq!(
+            solver.solve_flat(&bad),
+            Err(SolveError::InvalidDigit {
+                row: 1,
+                col: 1,
+                digit: 12
+            }),
+            "{:?}",
+            kind
+        );
+    }
+}
+
+#[test]
+fn a_parity_marking_picks_one_of_two_solutions() {
+    let solved = BacktrackingSudokuSolver::default()
+        .solve(&default_puzzle())
+        .unwrap();
+    // Blank a rectangle spanning two boxes whose corners hold an odd and an
+    // even digit crosswise: swapping the two digits gives the only other grid
+    let (rows, cols) = (0..9)
+        .flat_map(|r1| (r1 + 1..(r1 / 3 + 1) * 3).map(move |r2| (r1, r2)))
+        .flat_map(|rows| (0..9).flat_map(move |c1| (c1 + 1..9).map(move |c2| (rows, (c1, c2)))))
+        .find(|&((r1, r2), (c1, c2))| {
+            c1 / 3 != c2 / 3
+                && solved[r1][c1] == solved[r2][c2]
+                && solved[r1][c2] == solved[r2][c1]
+                && solved[r1][c1] % 2 != solved[r1][c2] % 2
+        })
+        .unwrap();
+    let mut puzzle = solved;
+    for row in [rows.0, rows.1] {
+        for col in [cols.0, cols.1] {
+            puzzle[row][col] = 0;
+        }
+    }
+    assert_eq!(backtracking::count_solutions(&puzzle, 3), 2);
+
+    // Marking the corner with either grid's parity there leaves just that grid
+    let corner = (rows.0, cols.0);
+    let mut swapped = solved;
+    swapped[rows.0].swap(cols.0, cols.1);
+    swapped[rows.1].swap(cols.0, cols.1);
+    for expected in [solved, swapped] {
+        let parity = if expected[corner.0][corner.1] % 2 == 1 {
+            Parity::Odd
+        } else {
+            Parity::Even
+        };
+        let parities = [(corner, parity)];
+        assert_eq!(
+            backtracking::solve_with_parities(&puzzle, &parities),
+            Ok(expected),
+            "{:?}",
+            parity
+        );
+        assert_eq!(
+            SatSudokuSolver::default().solve_with_parities(&puzzle, &parities),
+            Ok(expected),
+            "{:?}",
+            parity
+        );
+    }
+}
+
+#[test]
+fn parity_markings_off_the_grid_are_errors() {
+    let puzzle = default_puzzle();
+    for (cell, parity, digit) in [((9, 0), Parity::Odd, 1), ((0, 9), Parity::Even, 2)] {
+        let expected = Err(SolveError::InvalidPlacement {
+            row: cell.0,
+            col: cell.1,
+            digit,
+        });
+        let parities = [(cell, parity)];
+        assert_eq!(
+            backtracking::solve_with_parities(&puzzle, &parities),
+            expected
+        );
+        assert_eq!(
+            SatSudokuSolver::default().solve_with_parities(&puzzle, &parities),
+            expected
+        );
+    }
+}